@@ -1,7 +1,7 @@
 #![allow(unused)]
 use itertools::Itertools;
 
-fn parse(input: &str) -> Vec<Vec<i32>> {
+pub fn parse(input: &str) -> Vec<Vec<i32>> {
     let mut vec: Vec<Vec<i32>> = Vec::new();
     for line in input.lines() {
         let report = line.split(" ").map(|c| c.parse().unwrap()).collect_vec();
@@ -49,6 +49,133 @@ fn solution_b(vec: &[Vec<i32>]) -> usize {
         .count()
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    DirectionChange,
+    StepTooBig,
+    Flat,
+}
+
+impl Rule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rule::DirectionChange => "direction_change",
+            Rule::StepTooBig => "step_too_big",
+            Rule::Flat => "flat",
+        }
+    }
+}
+
+/// `pair` is the index of the first level of the offending pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub pair: usize,
+    pub rule: Rule,
+}
+
+/// `dampener` is the lowest level index whose removal makes an unsafe report safe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub violation: Option<Violation>,
+    pub dampener: Option<usize>,
+}
+
+impl Diagnosis {
+    pub fn is_safe(&self) -> bool {
+        self.violation.is_none()
+    }
+
+    pub fn is_safe_dampened(&self) -> bool {
+        self.is_safe() || self.dampener.is_some()
+    }
+
+    pub fn status(&self) -> &'static str {
+        if self.is_safe() {
+            "safe"
+        } else if self.dampener.is_some() {
+            "dampened"
+        } else {
+            "unsafe"
+        }
+    }
+}
+
+fn first_violation<'a>(report: impl Iterator<Item = &'a i32>) -> Option<Violation> {
+    let mut direction = None;
+    for (pair, (curr, next)) in report.tuple_windows().enumerate() {
+        let step = next - curr;
+        let rule = if step == 0 {
+            Some(Rule::Flat)
+        } else if direction.is_some_and(|dir| dir != step.signum()) {
+            Some(Rule::DirectionChange)
+        } else if step.abs() > 3 {
+            Some(Rule::StepTooBig)
+        } else {
+            None
+        };
+        if let Some(rule) = rule {
+            return Some(Violation { pair, rule });
+        }
+        direction = Some(step.signum());
+    }
+    None
+}
+
+pub fn diagnose(report: &[i32]) -> Diagnosis {
+    let violation = first_violation(report.iter());
+    let dampener = violation.and_then(|_| {
+        (0..report.len()).find(|&i| {
+            let (left, right) = report.split_at(i);
+            first_violation(left.iter().chain(right.iter().skip(1))).is_none()
+        })
+    });
+    Diagnosis {
+        violation,
+        dampener,
+    }
+}
+
+fn format_option(value: Option<impl ToString>, none: &str) -> String {
+    value.map_or(none.to_string(), |x| x.to_string())
+}
+
+pub fn diagnosis_table(reports: &[Vec<i32>]) -> String {
+    let mut table = format!(
+        "{:>6}  {:<8}  {:>4}  {:<16}  {:>4}\n",
+        "report", "status", "pair", "rule", "drop"
+    );
+    for (i, report) in reports.iter().enumerate() {
+        let diagnosis = diagnose(report);
+        table += &format!(
+            "{:>6}  {:<8}  {:>4}  {:<16}  {:>4}\n",
+            i,
+            diagnosis.status(),
+            format_option(diagnosis.violation.map(|v| v.pair), "-"),
+            format_option(diagnosis.violation.map(|v| v.rule.as_str()), "-"),
+            format_option(diagnosis.dampener, "-"),
+        );
+    }
+    table
+}
+
+pub fn diagnosis_json(reports: &[Vec<i32>]) -> String {
+    let entries = reports.iter().enumerate().map(|(i, report)| {
+        let diagnosis = diagnose(report);
+        let violation = diagnosis.violation.map_or("null".to_string(), |v| {
+            format!(r#"{{"pair":{},"rule":"{}"}}"#, v.pair, v.rule.as_str())
+        });
+        format!(
+            r#"{{"report":{},"levels":[{}],"status":"{}","violation":{},"dampener":{}}}"#,
+            i,
+            report.iter().join(","),
+            diagnosis.status(),
+            violation,
+            format_option(diagnosis.dampener, "null"),
+        )
+    });
+    format!("[{}]", entries.collect_vec().join(",\n "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,6 +192,54 @@ mod tests {
         println!("{}", solution_b(&vec));
     }
 
+    #[test]
+    fn diagnose_reports() {
+        let vec = parse(TEST_INPUT);
+        let diagnoses = vec.iter().map(|report| diagnose(report)).collect_vec();
+        assert_eq!(
+            diagnoses[0],
+            Diagnosis {
+                violation: None,
+                dampener: None
+            }
+        );
+        assert_eq!(
+            diagnoses[1].violation,
+            Some(Violation {
+                pair: 1,
+                rule: Rule::StepTooBig
+            })
+        );
+        assert_eq!(diagnoses[1].dampener, None);
+        assert_eq!(
+            diagnoses[3].violation,
+            Some(Violation {
+                pair: 1,
+                rule: Rule::DirectionChange
+            })
+        );
+        assert_eq!(diagnoses[3].dampener, Some(1));
+        assert_eq!(
+            diagnoses[4].violation,
+            Some(Violation {
+                pair: 2,
+                rule: Rule::Flat
+            })
+        );
+        assert_eq!(diagnoses[4].dampener, Some(2));
+
+        let vec = parse(INPUT);
+        let diagnoses = vec.iter().map(|report| diagnose(report)).collect_vec();
+        assert_eq!(
+            diagnoses.iter().filter(|d| d.is_safe()).count(),
+            solution_a(&vec)
+        );
+        assert_eq!(
+            diagnoses.iter().filter(|d| d.is_safe_dampened()).count(),
+            solution_b(&vec)
+        );
+    }
+
     const TEST_INPUT: &str = "7 6 4 2 1
 1 2 7 8 9
 9 7 6 2 1
@@ -0,0 +1,22 @@
+use day02::{diagnosis_json, diagnosis_table, parse};
+use std::{env, fs};
+
+const INPUT: &str = include_str!("input.txt");
+
+fn main() {
+    let mut json = false;
+    let mut path = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => path = Some(arg),
+        }
+    }
+    let input = path.map_or(INPUT.to_string(), |path| fs::read_to_string(path).unwrap());
+    let reports = parse(&input);
+    if json {
+        println!("{}", diagnosis_json(&reports));
+    } else {
+        print!("{}", diagnosis_table(&reports));
+    }
+}
@@ -3,90 +3,345 @@ use itertools::Itertools;
 use nom::{
     IResult,
     bytes::complete::tag,
-    character::complete::{self, char},
-    combinator::map,
-    sequence::{preceded, tuple},
+    character::complete::{char, digit1},
+    combinator::{map_res, verify},
+    multi::separated_list0,
+    sequence::terminated,
 };
-use regex::Regex;
-use std::fmt::{self};
-
-#[derive(fmt::Debug)]
-struct Pair {
-    a: i32,
-    b: i32,
-}
-
-#[derive(fmt::Debug)]
-enum Operation {
-    Multiply(Pair),
-    Dont,
-    Do,
-}
-
-fn parse(input: &str) -> Vec<Pair> {
-    let mut vec: Vec<Pair> = Vec::new();
-    let re = Regex::new(r"mul\((\d*),(\d*)\)").unwrap();
-    re.captures_iter(input)
-        .map(|x| {
-            let (_, [a, b]) = x.extract();
-            Pair {
-                a: a.parse().unwrap(),
-                b: b.parse().unwrap(),
+use std::{
+    io::{self, Read},
+    ops::Range,
+    str,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub span: Range<usize>,
+    pub name: &'a str,
+    pub args: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub enabled: bool,
+    pub total: i128,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine {
+            enabled: true,
+            total: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub execute: fn(&mut Machine, &[i64]),
+}
+
+pub const MUL: Instruction = Instruction {
+    name: "mul",
+    arity: 2,
+    execute: |machine, args| {
+        if machine.enabled {
+            machine.total += i128::from(args[0]) * i128::from(args[1]);
+        }
+    },
+};
+
+pub const ADD: Instruction = Instruction {
+    name: "add",
+    arity: 2,
+    execute: |machine, args| {
+        if machine.enabled {
+            machine.total += i128::from(args[0]) + i128::from(args[1]);
+        }
+    },
+};
+
+pub const SUB: Instruction = Instruction {
+    name: "sub",
+    arity: 2,
+    execute: |machine, args| {
+        if machine.enabled {
+            machine.total += i128::from(args[0]) - i128::from(args[1]);
+        }
+    },
+};
+
+pub const DO: Instruction = Instruction {
+    name: "do",
+    arity: 0,
+    execute: |machine, _| machine.enabled = true,
+};
+
+pub const DONT: Instruction = Instruction {
+    name: "don't",
+    arity: 0,
+    execute: |machine, _| machine.enabled = false,
+};
+
+pub const TOGGLE: Instruction = Instruction {
+    name: "toggle",
+    arity: 0,
+    execute: |machine, _| machine.enabled = !machine.enabled,
+};
+
+/// A call whose `name(` prefix matched but whose arguments were malformed or had the wrong
+/// arity. The span ends where parsing stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejected<'a> {
+    pub span: Range<usize>,
+    pub name: &'a str,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scan<'a> {
+    pub tokens: Vec<Token<'a>>,
+    pub rejected: Vec<Rejected<'a>>,
+}
+
+/// In strict mode operands must be 1 to 3 digits long, as the puzzle specifies.
+fn parse_operand<'a>(strict: bool) -> impl FnMut(&'a str) -> IResult<&'a str, i64> {
+    map_res(
+        verify(digit1, move |digits: &str| !strict || digits.len() <= 3),
+        str::parse,
+    )
+}
+
+fn parse_args(input: &str, strict: bool) -> IResult<&str, Vec<i64>> {
+    terminated(separated_list0(char(','), parse_operand(strict)), char(')'))(input)
+}
+
+fn parse_name<'a>(input: &'a str, name: &'static str) -> IResult<&'a str, &'a str> {
+    terminated(tag(name), char('('))(input)
+}
+
+/// Whether `args` is the start of an argument list of `arity` operands, so that a streaming
+/// scan should wait for more input rather than reject the call: at most `arity` groups of
+/// digits separated by commas, each no longer than an operand can be, and only the last
+/// one still empty.
+fn could_complete(args: &str, arity: usize, strict: bool) -> bool {
+    let max_digits = if strict { 3 } else { 19 };
+    let groups = args.split(',').collect_vec();
+    let (last, complete) = groups.split_last().unwrap();
+    groups.len() <= arity.max(1)
+        && (arity > 0 || args.is_empty())
+        && complete.iter().all(|group| !group.is_empty())
+        && groups
+            .iter()
+            .all(|group| group.len() <= max_digits && group.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Yields every well-formed call to one of `instructions`, skipping corrupted bytes.
+pub fn scan<'a>(input: &'a str, instructions: &[Instruction], strict: bool) -> Scan<'a> {
+    scan_until_incomplete(input, instructions, strict, false).0
+}
+
+/// With `partial` set, stops at the first call that more input could still complete and
+/// returns its offset alongside the calls before it; otherwise the whole input is consumed.
+fn scan_until_incomplete<'a>(
+    input: &'a str,
+    instructions: &[Instruction],
+    strict: bool,
+    partial: bool,
+) -> (Scan<'a>, usize) {
+    let mut scan = Scan::default();
+    let mut offset = 0;
+    while offset < input.len() {
+        let rest = &input[offset..];
+        let mut rejected = None;
+        let mut accepted = None;
+        for instruction in instructions {
+            if partial && instruction.name.starts_with(rest) {
+                return (scan, offset);
             }
-        })
-        .collect()
-}
-
-fn parse_b(input: &str) -> Vec<Operation> {
-    let mut vec: Vec<Pair> = Vec::new();
-    let re = Regex::new(r"do\(\)|don't\(\)|mul\(\d*,\d*\)").unwrap();
-    re.find_iter(input)
-        .map(|x| {
-            let text = x.as_str();
-            if text.starts_with("don't") {
-                Operation::Dont
-            } else if text.starts_with("do") {
-                Operation::Do
-            } else {
-                let pair = parse_pair(text);
-                Operation::Multiply(pair.unwrap().1)
+            let Ok((args_input, name)) = parse_name(rest, instruction.name) else {
+                continue;
+            };
+            // arguments cut off by the end of the input may still become valid
+            if partial && could_complete(args_input, instruction.arity, strict) {
+                return (scan, offset);
             }
-        })
-        .collect()
-}
-
-fn parse_pair(text: &str) -> IResult<&str, Pair> {
-    let pair = map(
-        preceded(
-            tag("mul"),
-            tuple((
-                char('('),
-                complete::i32,
-                char(','),
-                complete::i32,
-                char(')'),
-            )),
-        ),
-        |x| Pair { a: x.1, b: x.3 },
-    )(text);
-    pair
-}
-
-fn solution_a(vec: &[Pair]) -> i32 {
-    vec.iter().map(|p| p.a * p.b).sum()
-}
-
-fn solution_b(vec: &[Operation]) -> i32 {
-    let mut condition = true;
-    let mut res = 0;
-    for operation in vec {
-        match operation {
-            Operation::Multiply(pair) => res += if condition { pair.a * pair.b } else { 0 },
-            Operation::Dont => condition = false,
-            Operation::Do => condition = true,
+            match parse_args(args_input, strict) {
+                Ok((remaining, args)) if args.len() == instruction.arity => {
+                    accepted = Some((remaining, name, args));
+                    break;
+                }
+                Ok((remaining, _)) => {
+                    rejected.get_or_insert((remaining, name));
+                }
+                Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+                    rejected.get_or_insert((error.input, name));
+                }
+                Err(nom::Err::Incomplete(_)) => unreachable!(),
+            }
+        }
+        if let Some((remaining, name, args)) = accepted {
+            let end = input.len() - remaining.len();
+            scan.tokens.push(Token {
+                span: offset..end,
+                name,
+                args,
+            });
+            offset = end;
+        } else {
+            if let Some((remaining, name)) = rejected {
+                scan.rejected.push(Rejected {
+                    span: offset..input.len() - remaining.len(),
+                    name,
+                });
+            }
+            offset += rest.chars().next().unwrap().len_utf8();
         }
     }
-    res
+    (scan, offset)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Interpreter {
+    instructions: Vec<Instruction>,
+    strict: bool,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn scan<'a>(&self, input: &'a str) -> Scan<'a> {
+        scan(input, &self.instructions, self.strict)
+    }
+
+    /// Only for tokens this interpreter scanned itself, which always have a registered
+    /// instruction.
+    fn execute(&self, machine: &mut Machine, token: &Token) {
+        let instruction = self
+            .instructions
+            .iter()
+            .find(|instruction| {
+                instruction.name == token.name && instruction.arity == token.args.len()
+            })
+            .unwrap();
+        (instruction.execute)(machine, &token.args);
+    }
+
+    pub fn run(&self, input: &str) -> Machine {
+        let mut machine = Machine::default();
+        for token in self.scan(input).tokens {
+            self.execute(&mut machine, &token);
+        }
+        machine
+    }
+
+    /// Evaluates `reader` in chunks of `chunk_size` bytes, carrying calls split across chunk
+    /// boundaries over to the next chunk.
+    pub fn run_reader(&self, mut reader: impl Read, chunk_size: usize) -> io::Result<Machine> {
+        let mut machine = Machine::default();
+        let mut pending = Vec::new();
+        let mut chunk = vec![0; chunk_size];
+        loop {
+            let read = reader.read(&mut chunk)?;
+            pending.extend_from_slice(&chunk[..read]);
+            let text = match str::from_utf8(&pending) {
+                Ok(text) => text,
+                // a multi-byte character split by the chunk boundary
+                Err(error) if error.error_len().is_none() && read > 0 => {
+                    str::from_utf8(&pending[..error.valid_up_to()]).unwrap()
+                }
+                Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+            };
+            let (scan, consumed) =
+                scan_until_incomplete(text, &self.instructions, self.strict, read > 0);
+            for token in scan.tokens {
+                self.execute(&mut machine, &token);
+            }
+            pending.drain(..consumed);
+            if read == 0 {
+                return Ok(machine);
+            }
+        }
+    }
+
+    /// Lists every accepted and rejected call with its byte offset, and how it changed the machine.
+    pub fn explain(&self, input: &str) -> String {
+        let scan = self.scan(input);
+        let accepted = scan
+            .tokens
+            .iter()
+            .map(|token| (token.span.clone(), Some(token)));
+        let rejected = scan
+            .rejected
+            .iter()
+            .map(|rejected| (rejected.span.clone(), None));
+        let mut machine = Machine::default();
+        let mut explanation = String::new();
+        for (span, token) in accepted.merge_by(rejected, |a, b| a.0.start <= b.0.start) {
+            let effect = if let Some(token) = token {
+                let before = machine.clone();
+                self.execute(&mut machine, token);
+                if before.enabled != machine.enabled {
+                    format!(
+                        "{} -> {}",
+                        state_name(before.enabled),
+                        state_name(machine.enabled)
+                    )
+                } else if before.total != machine.total {
+                    format!(
+                        "{}  {:+}",
+                        state_name(machine.enabled),
+                        machine.total - before.total
+                    )
+                } else {
+                    format!("{}  skipped", state_name(machine.enabled))
+                }
+            } else {
+                String::from("rejected")
+            };
+            explanation += &format!(
+                "{:>8}  {:<16}  {}\n",
+                span.start,
+                &input[span.clone()],
+                effect
+            );
+        }
+        explanation += &format!("total {}\n", machine.total);
+        explanation
+    }
+}
+
+fn state_name(enabled: bool) -> &'static str {
+    if enabled { "active" } else { "inactive" }
+}
+
+pub fn part_a() -> Interpreter {
+    Interpreter::new().register(MUL)
+}
+
+pub fn part_b() -> Interpreter {
+    Interpreter::new().register(MUL).register(DO).register(DONT)
+}
+
+pub fn solution_a(input: &str) -> i128 {
+    part_a().run(input).total
+}
+
+pub fn solution_b(input: &str) -> i128 {
+    part_b().run(input).total
 }
 
 #[cfg(test)]
@@ -95,17 +350,135 @@ mod tests {
 
     #[test]
     fn it_works() {
-        let vec = parse(TEST_INPUT);
-        println!("{} {:?}", vec.len(), vec);
-        assert_eq!(solution_a(&vec), 161);
-        let vec = parse(INPUT);
-        println!("{}", solution_a(&vec));
-
-        let vec = parse_b(TEST_INPUT_B);
-        println!("{} {:?}", vec.len(), vec);
-        assert_eq!(solution_b(&vec), 48);
-        let vec = parse_b(INPUT);
-        println!("{}", solution_b(&vec));
+        let tokens = part_a().scan(TEST_INPUT).tokens;
+        println!("{} {:?}", tokens.len(), tokens);
+        assert_eq!(solution_a(TEST_INPUT), 161);
+        println!("{}", solution_a(INPUT));
+
+        let tokens = part_b().scan(TEST_INPUT_B).tokens;
+        println!("{} {:?}", tokens.len(), tokens);
+        assert_eq!(solution_b(TEST_INPUT_B), 48);
+        println!("{}", solution_b(INPUT));
+    }
+
+    #[test]
+    fn custom_instructions() {
+        let interpreter = part_b().register(ADD).register(SUB).register(TOGGLE);
+        let tokens = interpreter
+            .scan("xadd(2,3)don't()mul(4,4)toggle()sub(10,4)undo()mul(,5)")
+            .tokens;
+        assert_eq!(
+            tokens.iter().map(|token| token.name).collect_vec(),
+            vec!["add", "don't", "mul", "toggle", "sub", "do"]
+        );
+        assert_eq!(tokens[0].span, 1..9);
+        assert_eq!(
+            interpreter
+                .run("xadd(2,3)don't()mul(4,4)toggle()sub(10,4)")
+                .total,
+            11
+        );
+    }
+
+    #[test]
+    fn strict_mode() {
+        let input = "mul(,5)mul(1234,5)mul(123,4)mul(2,3,4)mul(32,64]mul(7,8)";
+        let scan = part_a().strict().scan(input);
+        assert_eq!(
+            scan.tokens
+                .iter()
+                .map(|token| token.span.start)
+                .collect_vec(),
+            vec![18, 48]
+        );
+        assert_eq!(
+            scan.rejected
+                .iter()
+                .map(|rejected| rejected.span.clone())
+                .collect_vec(),
+            vec![0..4, 7..11, 28..38, 38..47]
+        );
+        assert_eq!(part_a().strict().run(input).total, 123 * 4 + 7 * 8);
+        assert_eq!(part_a().run(input).total, 1234 * 5 + 123 * 4 + 7 * 8);
+
+        assert_eq!(part_b().strict().run(INPUT), part_b().run(INPUT));
+        println!("{}", part_b().strict().explain(TEST_INPUT_B));
+    }
+
+    #[test]
+    fn streaming() {
+        for chunk_size in [1, 2, 3, 5, 7, 64, 4096] {
+            assert_eq!(
+                part_a()
+                    .run_reader(TEST_INPUT.as_bytes(), chunk_size)
+                    .unwrap(),
+                part_a().run(TEST_INPUT)
+            );
+            assert_eq!(
+                part_b()
+                    .run_reader(TEST_INPUT_B.as_bytes(), chunk_size)
+                    .unwrap(),
+                part_b().run(TEST_INPUT_B)
+            );
+            assert_eq!(
+                part_b()
+                    .strict()
+                    .run_reader(INPUT.as_bytes(), chunk_size)
+                    .unwrap(),
+                part_b().strict().run(INPUT)
+            );
+        }
+        let input = "mul(1234,5)dodon't()mul(99999999999,99999999999)do()mul(2,3)é";
+        for chunk_size in 1..input.len() {
+            assert_eq!(
+                part_b().run_reader(input.as_bytes(), chunk_size).unwrap(),
+                part_b().run(input)
+            );
+            assert_eq!(
+                part_b()
+                    .strict()
+                    .run_reader(input.as_bytes(), chunk_size)
+                    .unwrap(),
+                part_b().strict().run(input)
+            );
+        }
+        assert_eq!(
+            part_a().run(input).total,
+            99999999999 * 99999999999 + 1234 * 5 + 6
+        );
+
+        // a corrupted call that never closes must not hold up the rest of the stream
+        let input = format!(
+            "mul(2,{}mul(3,4)mul(1{})",
+            "7".repeat(1_000_000),
+            ",5".repeat(1000)
+        );
+        for chunk_size in [7, 4096] {
+            assert_eq!(
+                part_a()
+                    .run_reader(input.as_bytes(), chunk_size)
+                    .unwrap()
+                    .total,
+                12
+            );
+            assert_eq!(
+                part_a()
+                    .strict()
+                    .run_reader(input.as_bytes(), chunk_size)
+                    .unwrap()
+                    .total,
+                12
+            );
+        }
+        assert!(could_complete("12,4", 2, true));
+        assert!(could_complete("12,", 2, true));
+        assert!(could_complete("", 0, true));
+        assert!(!could_complete("1234", 2, true));
+        assert!(could_complete("1234", 2, false));
+        assert!(!could_complete(&"1".repeat(20), 2, false));
+        assert!(!could_complete("1,2,", 2, false));
+        assert!(!could_complete(",2", 2, false));
+        assert!(!could_complete("1", 0, false));
     }
 
     const TEST_INPUT: &str =
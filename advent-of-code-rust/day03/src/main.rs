@@ -0,0 +1,29 @@
+use day03::{part_a, part_b};
+use std::{env, fs};
+
+const INPUT: &str = include_str!("input.txt");
+
+fn main() {
+    let mut strict = false;
+    let mut explain = false;
+    let mut path = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--strict" => strict = true,
+            "--explain" => explain = true,
+            _ => path = Some(arg),
+        }
+    }
+    let input = path.map_or(INPUT.to_string(), |path| fs::read_to_string(path).unwrap());
+    let (part_a, part_b) = if strict {
+        (part_a().strict(), part_b().strict())
+    } else {
+        (part_a(), part_b())
+    };
+    if explain {
+        print!("{}", part_b.explain(&input));
+    } else {
+        println!("{}", part_a.run(&input).total);
+        println!("{}", part_b.run(&input).total);
+    }
+}
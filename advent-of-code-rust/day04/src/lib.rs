@@ -1,76 +1,409 @@
 #![allow(unused)]
+use aho_corasick::AhoCorasick;
 use itertools::Itertools;
+use std::{collections::HashSet, fs, io, path::Path};
 
-fn parse(input: &str) -> Vec<Vec<char>> {
+pub fn parse(input: &str) -> Vec<Vec<char>> {
     input.lines().map(|line| line.chars().collect()).collect()
 }
 
-fn solution_a(grid: &Vec<Vec<char>>) -> u32 {
-    let mut count = 0;
-    for y in 0..grid.len() - 3 {
-        for x in 0..grid[0].len() - 3 {
-            if (is_xmas(
-                grid[y][x],
-                grid[y + 1][x + 1],
-                grid[y + 2][x + 2],
-                grid[y + 3][x + 3],
-            )) {
-                // right down diagonal
-                count += 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// (dx, dy) with y growing downwards.
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WordMatch<'a> {
+    pub x: usize,
+    pub y: usize,
+    pub direction: Direction,
+    pub word: &'a str,
+}
+
+impl WordMatch<'_> {
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (dx, dy) = self.direction.delta();
+        (0..self.word.chars().count() as isize).map(move |i| {
+            (
+                self.x.wrapping_add_signed(dx * i),
+                self.y.wrapping_add_signed(dy * i),
+            )
+        })
+    }
+}
+
+fn step(
+    grid: &[Vec<char>],
+    (x, y): (usize, usize),
+    (dx, dy): (isize, isize),
+) -> Option<(usize, usize)> {
+    let x = x.checked_add_signed(dx)?;
+    let y = y.checked_add_signed(dy)?;
+    (y < grid.len() && x < grid[y].len()).then_some((x, y))
+}
+
+/// Every straight line through `grid` in `direction`, as its text and the cell of each byte.
+fn rays(grid: &[Vec<char>], direction: Direction) -> Vec<(String, Vec<(usize, usize)>)> {
+    let (dx, dy) = direction.delta();
+    let starts = grid
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| (0..row.len()).map(move |x| (x, y)))
+        .filter(|&cell| step(grid, cell, (-dx, -dy)).is_none());
+    starts
+        .map(|start| {
+            let mut text = String::new();
+            let mut cells = Vec::new();
+            let mut cell = Some(start);
+            while let Some((x, y)) = cell {
+                text.push(grid[y][x]);
+                cells.extend(std::iter::repeat_n((x, y), grid[y][x].len_utf8()));
+                cell = step(grid, (x, y), (dx, dy));
             }
-            if (is_xmas(
-                grid[y + 3][x],
-                grid[y + 2][x + 1],
-                grid[y + 1][x + 2],
-                grid[y][x + 3],
-            )) {
-                // up right diagonal
-                count += 1;
+            (text, cells)
+        })
+        .collect()
+}
+
+/// Finds every occurrence of `words` reading in any of the 8 directions, in a single
+/// Aho-Corasick pass per direction. Empty words are ignored, and a one-letter word reads
+/// the same every way, so it is only reported reading East.
+pub fn find_words<'a>(grid: &[Vec<char>], words: &[&'a str]) -> Vec<WordMatch<'a>> {
+    let words = words
+        .iter()
+        .copied()
+        .filter(|word| !word.is_empty())
+        .collect_vec();
+    let automaton = AhoCorasick::new(&words).unwrap();
+    let mut matches = Vec::new();
+    for direction in Direction::ALL {
+        for (text, cells) in rays(grid, direction) {
+            for found in automaton.find_overlapping_iter(&text) {
+                let word = words[found.pattern().as_usize()];
+                if word.chars().count() == 1 && direction != Direction::East {
+                    continue;
+                }
+                let (x, y) = cells[found.start()];
+                matches.push(WordMatch {
+                    x,
+                    y,
+                    direction,
+                    word,
+                });
             }
         }
     }
-    for y in 0..grid.len() - 3 {
-        for x in 0..grid[0].len() {
-            if (is_xmas(grid[y][x], grid[y + 1][x], grid[y + 2][x], grid[y + 3][x])) {
-                // vertical
-                count += 1;
+    matches
+}
+
+fn solution_a(grid: &[Vec<char>]) -> u32 {
+    find_words(grid, &["XMAS"]).len() as u32
+}
+
+pub const X_MAS: &str = "M.S
+.A.
+M.S";
+
+pub const PLUS_MAS: &str = ".M.
+MAS
+.S.";
+
+/// A small 2D pattern; `None` cells are wildcards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stencil {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+impl Stencil {
+    pub const WILDCARD: char = '.';
+
+    pub fn parse(pattern: &str) -> Stencil {
+        Stencil {
+            cells: pattern
+                .lines()
+                .map(|line| {
+                    line.chars()
+                        .map(|c| (c != Stencil::WILDCARD).then_some(c))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.iter().map(|row| row.len()).max().unwrap_or(0)
+    }
+
+    fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.cells[y].get(x).copied().flatten()
+    }
+
+    /// Offsets of the non-wildcard cells relative to the top left corner.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+        self.cells.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter_map(move |(x, c)| c.map(|c| (x, y, c)))
+        })
+    }
+
+    /// Rotates 90 degrees clockwise.
+    pub fn rotate(&self) -> Stencil {
+        let (width, height) = (self.width(), self.height());
+        Stencil {
+            cells: (0..width)
+                .map(|x| (0..height).rev().map(|y| self.get(x, y)).collect())
+                .collect(),
+        }
+    }
+
+    /// Mirrors left to right.
+    pub fn reflect(&self) -> Stencil {
+        let width = self.width();
+        Stencil {
+            cells: (0..self.height())
+                .map(|y| (0..width).rev().map(|x| self.get(x, y)).collect())
+                .collect(),
+        }
+    }
+
+    /// The distinct rotations and reflections of this stencil.
+    pub fn variants(&self) -> Vec<Stencil> {
+        let mut variants: Vec<Stencil> = Vec::new();
+        for mut stencil in [self.clone(), self.reflect()] {
+            for _ in 0..4 {
+                if !variants.contains(&stencil) {
+                    variants.push(stencil.clone());
+                }
+                stencil = stencil.rotate();
             }
         }
+        variants
+    }
+
+    pub fn matches_at(&self, grid: &[Vec<char>], x: usize, y: usize) -> bool {
+        self.cells()
+            .all(|(dx, dy, c)| grid.get(y + dy).and_then(|row| row.get(x + dx)) == Some(&c))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StencilMatch {
+    pub x: usize,
+    pub y: usize,
+    pub stencil: Stencil,
+}
+
+impl StencilMatch {
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.stencil
+            .cells()
+            .map(|(dx, dy, _)| (self.x + dx, self.y + dy))
     }
-    for y in 0..grid.len() {
-        for x in 0..grid[0].len() - 3 {
-            if (is_xmas(grid[y][x], grid[y][x + 1], grid[y][x + 2], grid[y][x + 3])) {
-                // horizontal
-                count += 1;
+}
+
+/// Finds every placement of `stencil`, also trying its rotations and reflections when
+/// `symmetric` is set.
+pub fn find_stencil(grid: &[Vec<char>], stencil: &Stencil, symmetric: bool) -> Vec<StencilMatch> {
+    let variants = if symmetric {
+        stencil.variants()
+    } else {
+        vec![stencil.clone()]
+    };
+    let mut matches = Vec::new();
+    for (y, row) in grid.iter().enumerate() {
+        for x in 0..row.len() {
+            for variant in &variants {
+                if variant.matches_at(grid, x, y) {
+                    matches.push(StencilMatch {
+                        x,
+                        y,
+                        stencil: variant.clone(),
+                    });
+                }
             }
         }
     }
-    count
+    matches
 }
 
-fn is_xmas(a: char, b: char, c: char, d: char) -> bool {
-    a == 'X' && b == 'M' && c == 'A' && d == 'S' || d == 'X' && c == 'M' && b == 'A' && a == 'S'
+fn solution_b(grid: &[Vec<char>]) -> u32 {
+    find_stencil(grid, &Stencil::parse(X_MAS), true).len() as u32
 }
 
-fn is_mas(a: char, b: char, c: char) -> bool {
-    a == 'M' && b == 'A' && c == 'S' || c == 'M' && b == 'A' && a == 'S'
+fn solution_b_plus(grid: &[Vec<char>]) -> u32 {
+    find_stencil(grid, &Stencil::parse(PLUS_MAS), true).len() as u32
+}
+
+/// Anything found in the grid that can be drawn by the renderers.
+pub trait Highlight {
+    fn highlighted_cells(&self) -> Vec<(usize, usize)>;
+}
+
+impl Highlight for WordMatch<'_> {
+    fn highlighted_cells(&self) -> Vec<(usize, usize)> {
+        self.cells().collect()
+    }
+}
+
+impl Highlight for StencilMatch {
+    fn highlighted_cells(&self) -> Vec<(usize, usize)> {
+        self.cells().collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Matched cells in bold red, everything else as is.
+    Ansi,
+    /// Matched cells as is, everything else blanked with '.'.
+    Plain,
+}
+
+fn highlighted<M: Highlight>(matches: &[M]) -> HashSet<(usize, usize)> {
+    matches.iter().flat_map(|m| m.highlighted_cells()).collect()
+}
+
+/// Renders the grid with the cells of `matches` highlighted; pass a one-element slice to
+/// look at a single match.
+pub fn render<M: Highlight>(grid: &[Vec<char>], matches: &[M], mode: RenderMode) -> String {
+    let cells = highlighted(matches);
+    let mut rendered = String::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            match (mode, cells.contains(&(x, y))) {
+                (RenderMode::Ansi, true) => rendered += &format!("\x1b[1;31m{}\x1b[0m", c),
+                (RenderMode::Plain, false) => rendered.push('.'),
+                _ => rendered.push(*c),
+            }
+        }
+        rendered.push('\n');
+    }
+    rendered
 }
 
-fn solution_b(grid: &Vec<Vec<char>>) -> u32 {
-    let mut count = 0;
-    for y in 0..grid.len() - 2 {
-        for x in 0..grid[0].len() - 2 {
-            let is_right_down_diagonal = is_mas(grid[y][x], grid[y + 1][x + 1], grid[y + 2][x + 2]);
-            let is_up_right_diagonal = is_mas(grid[y + 2][x], grid[y + 1][x + 1], grid[y][x + 2]);
-            if (is_right_down_diagonal && is_up_right_diagonal) {
-                count += 1;
+fn render_html_frame(grid: &[Vec<char>], cells: &HashSet<(usize, usize)>) -> String {
+    let mut frame = String::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            let escaped = match c {
+                '<' => "&lt;".to_string(),
+                '>' => "&gt;".to_string(),
+                '&' => "&amp;".to_string(),
+                _ => c.to_string(),
+            };
+            if cells.contains(&(x, y)) {
+                frame += &format!("<mark>{}</mark>", escaped);
+            } else {
+                frame += &escaped;
             }
-            let is_middle_vertical = is_mas(grid[y][x + 1], grid[y + 1][x + 1], grid[y + 2][x + 1]);
-            let is_middle_horizontal =
-                is_mas(grid[y + 1][x], grid[y + 1][x + 1], grid[y + 1][x + 2]);
         }
+        frame.push('\n');
     }
-    count
+    frame
+}
+
+/// Renders a standalone HTML page. With `one_at_a_time` every match gets its own frame,
+/// stepped through with the previous/next buttons.
+pub fn render_html<M: Highlight>(grid: &[Vec<char>], matches: &[M], one_at_a_time: bool) -> String {
+    let frames = if one_at_a_time {
+        matches
+            .iter()
+            .map(|m| render_html_frame(grid, &m.highlighted_cells().into_iter().collect()))
+            .collect_vec()
+    } else {
+        vec![render_html_frame(grid, &highlighted(matches))]
+    };
+    let body = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let hidden = if i == 0 { "" } else { " hidden" };
+            format!(r#"<pre class="frame"{}>{}</pre>"#, hidden, frame)
+        })
+        .join("\n");
+    let controls = if one_at_a_time {
+        r#"<p><button onclick="step(-1)">previous</button> <span id="counter"></span> <button onclick="step(1)">next</button></p>
+<script>
+const frames = document.querySelectorAll(".frame");
+let current = 0;
+function step(delta) {
+  if (frames.length === 0) return;
+  frames[current].hidden = true;
+  current = (current + delta + frames.length) % frames.length;
+  frames[current].hidden = false;
+  document.getElementById("counter").textContent = `${current + 1} / ${frames.length}`;
+}
+step(0);
+</script>"#
+    } else {
+        ""
+    };
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>pre {{ font-size: 1.2em; }} mark {{ background: #f66; }}</style>
+</head>
+<body>
+<p>{} matches</p>
+{}
+{}
+</body>
+</html>
+"#,
+        matches.len(),
+        body,
+        controls
+    )
+}
+
+pub fn export_html<M: Highlight>(
+    path: impl AsRef<Path>,
+    grid: &[Vec<char>],
+    matches: &[M],
+    one_at_a_time: bool,
+) -> io::Result<()> {
+    fs::write(path, render_html(grid, matches, one_at_a_time))
 }
 
 #[cfg(test)]
@@ -88,6 +421,111 @@ mod tests {
         assert_eq!(solution_b(&grid), 9);
         let grid = parse(INPUT);
         println!("{}", solution_b(&grid));
+        println!("{}", solution_b_plus(&grid));
+    }
+
+    #[test]
+    fn word_search() {
+        let grid = parse(TEST_INPUT);
+        let matches = find_words(&grid, &["XMAS", "SAMX", "MAX"]);
+        assert_eq!(matches.iter().filter(|m| m.word == "XMAS").count(), 18);
+        assert_eq!(matches.iter().filter(|m| m.word == "SAMX").count(), 18);
+        assert!(matches.contains(&WordMatch {
+            x: 5,
+            y: 0,
+            direction: Direction::East,
+            word: "XMAS",
+        }));
+        let max = matches.iter().find(|m| m.word == "MAX").unwrap();
+        assert_eq!(
+            max.cells().map(|(x, y)| grid[y][x]).collect::<String>(),
+            "MAX"
+        );
+
+        assert_eq!(solution_a(&parse("")), 0);
+        assert_eq!(solution_a(&parse("XMA\nMAS")), 0);
+        assert_eq!(solution_a(&parse("X\nM\nA\nS")), 1);
+        assert_eq!(find_words(&parse("XMAS"), &["XMAS", "AS"]).len(), 2);
+
+        assert!(find_words(&parse("XM"), &[""]).is_empty());
+        assert_eq!(find_words(&parse("XM"), &["", "M"]).len(), 1);
+        let letters = find_words(
+            &parse(
+                "AXA
+MAS",
+            ),
+            &["A"],
+        );
+        assert_eq!(letters.len(), 3);
+        assert!(letters.iter().all(|m| m.direction == Direction::East));
+    }
+
+    #[test]
+    fn stencils() {
+        let x_mas = Stencil::parse(X_MAS);
+        assert_eq!(x_mas.variants().len(), 4);
+        assert_eq!(x_mas.rotate().rotate().rotate().rotate(), x_mas);
+        assert_eq!(Stencil::parse(PLUS_MAS).variants().len(), 4);
+        assert_eq!(Stencil::parse("XMAS").variants().len(), 4);
+        assert_eq!(Stencil::parse("AB\nC.").rotate(), Stencil::parse("CA\n.B"));
+        assert_eq!(Stencil::parse("AB\nC.").reflect(), Stencil::parse("BA\n.C"));
+
+        let grid = parse(".MM.S\nMASA.\n.SM.S");
+        let plus = find_stencil(&grid, &Stencil::parse(PLUS_MAS), true);
+        assert_eq!(plus.len(), 1);
+        assert_eq!((plus[0].x, plus[0].y), (0, 0));
+        assert_eq!(
+            plus[0].cells().collect_vec(),
+            vec![(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)]
+        );
+        let x = find_stencil(&grid, &Stencil::parse(X_MAS), true);
+        assert_eq!(x.iter().map(|m| (m.x, m.y)).collect_vec(), vec![(2, 0)]);
+        assert_eq!(find_stencil(&grid, &Stencil::parse(X_MAS), false).len(), 1);
+        assert!(find_stencil(&grid, &Stencil::parse(X_MAS).rotate(), false).is_empty());
+
+        assert_eq!(solution_b(&parse("")), 0);
+        assert_eq!(solution_b(&parse("MS\nAA")), 0);
+        assert_eq!(solution_b_plus(&parse(TEST_INPUT)), 0);
+        let grid = parse(TEST_INPUT);
+        let straight = find_words(&grid, &["XMAS"])
+            .into_iter()
+            .filter(|m| {
+                let (dx, dy) = m.direction.delta();
+                dx == 0 || dy == 0
+            })
+            .count();
+        assert_eq!(
+            find_stencil(&grid, &Stencil::parse("XMAS"), true).len(),
+            straight
+        );
+    }
+
+    #[test]
+    fn rendering() {
+        let grid = parse("XMASX\nAAMSS\nSSAMA");
+        let words = find_words(&grid, &["XMAS"]);
+        assert_eq!(
+            render(&grid, &words, RenderMode::Plain),
+            "XMAS.\n.....\n.....\n"
+        );
+        let stencils = find_stencil(&grid, &Stencil::parse("MS"), false);
+        assert_eq!(
+            render(&grid, &stencils, RenderMode::Plain),
+            ".....\n..MS.\n.....\n"
+        );
+        assert_eq!(
+            render(&grid[..1], &words, RenderMode::Ansi),
+            "\x1b[1;31mX\x1b[0m\x1b[1;31mM\x1b[0m\x1b[1;31mA\x1b[0m\x1b[1;31mS\x1b[0mX\n"
+        );
+
+        let grid = parse(TEST_INPUT);
+        let words = find_words(&grid, &["XMAS"]);
+        let html = render_html(&grid, &words, false);
+        assert_eq!(html.matches("<pre").count(), 1);
+        let html = render_html(&grid, &words, true);
+        assert_eq!(html.matches("<pre").count(), 18);
+        assert_eq!(html.matches("<mark>").count(), 18 * 4);
+        println!("{}", render(&grid, &words, RenderMode::Ansi));
     }
 
     const TEST_INPUT: &str = "MMMSXXMASM
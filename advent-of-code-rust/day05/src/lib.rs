@@ -4,24 +4,24 @@ use itertools::Itertools;
 use nom::{
     IResult,
     bytes::complete::tag,
-    character::complete::{self, newline},
+    character::complete::{self, line_ending},
     combinator::map,
     multi::separated_list1,
-    sequence::separated_pair,
+    sequence::{pair, separated_pair},
 };
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     time::Instant,
 };
 
-fn parse_rules(input: &str) -> IResult<&str, Vec<(u32, u32)>> {
+pub fn parse_rules(input: &str) -> IResult<&str, Vec<(u32, u32)>> {
     separated_list1(
-        newline,
+        line_ending,
         separated_pair(complete::u32, tag("|"), complete::u32),
     )(input)
 }
 
-fn to_rule_map(rules: Vec<(u32, u32)>) -> HashMap<u32, Vec<u32>> {
+pub fn to_rule_map(rules: Vec<(u32, u32)>) -> HashMap<u32, Vec<u32>> {
     let mut map = HashMap::new();
     for (parent, child) in rules {
         map.entry(child).or_insert(vec![]).push(parent);
@@ -29,16 +29,23 @@ fn to_rule_map(rules: Vec<(u32, u32)>) -> HashMap<u32, Vec<u32>> {
     map
 }
 
-fn parse_updates(input: &str) -> IResult<&str, Vec<Vec<u32>>> {
-    separated_list1(newline, separated_list1(tag(","), complete::u32))(input)
+pub fn parse_updates(input: &str) -> IResult<&str, Vec<Vec<u32>>> {
+    separated_list1(line_ending, separated_list1(tag(","), complete::u32))(input)
 }
 
-fn parse(rules_str: &str, updates_str: &str) -> (Vec<Vec<u32>>, HashMap<u32, Vec<u32>>) {
+pub fn parse(rules_str: &str, updates_str: &str) -> (Vec<Vec<u32>>, HashMap<u32, Vec<u32>>) {
     let rules = to_rule_map(parse_rules(rules_str).unwrap().1);
     let updates = parse_updates(updates_str).unwrap().1;
     (updates, rules)
 }
-fn validate_update(update: &Vec<u32>, rules: &HashMap<u32, Vec<u32>>) -> Option<u32> {
+
+/// The puzzle's single-file format: the rules, a blank line, then the updates.
+pub fn parse_input(input: &str) -> (Vec<Vec<u32>>, HashMap<u32, Vec<u32>>) {
+    let (_, (rules, updates)) =
+        separated_pair(parse_rules, pair(line_ending, line_ending), parse_updates)(input).unwrap();
+    (updates, to_rule_map(rules))
+}
+fn validate_update(update: &[u32], rules: &HashMap<u32, Vec<u32>>) -> Option<u32> {
     let mut active_rules = HashSet::<u32>::new();
     for number in update.iter().rev() {
         if !active_rules.is_empty() {
@@ -62,6 +69,35 @@ fn validate_update(update: &Vec<u32>, rules: &HashMap<u32, Vec<u32>>) -> Option<
     }
 }
 
+/// A broken rule `before|after`: `before` sits at `before_position`, after `after`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleViolation {
+    pub before: u32,
+    pub after: u32,
+    pub before_position: usize,
+    pub after_position: usize,
+}
+
+pub fn explain_update(update: &[u32], rules: &HashMap<u32, Vec<u32>>) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for (after_position, after) in update.iter().enumerate() {
+        let Some(parents) = rules.get(after) else {
+            continue;
+        };
+        for (before_position, before) in update.iter().enumerate().skip(after_position + 1) {
+            if parents.contains(before) {
+                violations.push(RuleViolation {
+                    before: *before,
+                    after: *after,
+                    before_position,
+                    after_position,
+                });
+            }
+        }
+    }
+    violations
+}
+
 fn solution_a(updates: Vec<Vec<u32>>, rules: HashMap<u32, Vec<u32>>) -> u32 {
     let mut res = 0;
     for update in updates {
@@ -72,56 +108,477 @@ fn solution_a(updates: Vec<Vec<u32>>, rules: HashMap<u32, Vec<u32>>) -> u32 {
     res
 }
 
-fn create_valid_path(update: &Vec<u32>, rules: &HashMap<u32, Vec<u32>>) -> Vec<u32> {
-    let mut parent_to_child = HashMap::<u32, Vec<u32>>::new();
-    for number in update.iter() {
-        if let Some(parents) = rules.get(&number) {
-            for parent in parents {
-                if update.contains(parent) {
-                    parent_to_child.entry(*parent).or_insert(vec![]).push(*number);
-                }
-            }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// The rules restricted to the update contain this cycle, in rule order.
+    Cycle(Vec<u32>),
+    /// More than one ordering satisfies the rules; these pages were interchangeable.
+    Ambiguous(Vec<u32>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ordering {
+    pub pages: Vec<u32>,
+    /// The first set of pages that could have come next in any order, if there was one.
+    pub ambiguous: Option<Vec<u32>>,
+}
+
+impl Ordering {
+    pub fn is_unique(&self) -> bool {
+        self.ambiguous.is_none()
+    }
+}
+
+/// Walks rules backwards from a page with unmet dependencies until a page repeats.
+fn find_cycle(remaining: &HashSet<u32>, parents: &HashMap<u32, Vec<u32>>, start: u32) -> Vec<u32> {
+    let mut path = vec![start];
+    let mut seen = HashMap::from([(start, 0)]);
+    let mut page = start;
+    loop {
+        page = *parents[&page]
+            .iter()
+            .find(|parent| remaining.contains(parent))
+            .unwrap();
+        if let Some(&i) = seen.get(&page) {
+            let mut cycle = path.split_off(i);
+            cycle.reverse();
+            return cycle;
         }
+        seen.insert(page, path.len());
+        path.push(page);
     }
-    let mut child_to_parent = HashMap::new();
-    for (parent, children) in &parent_to_child {
-        for child in children {
-            child_to_parent.entry(child).or_insert(vec![]).push(parent);
+}
+
+/// Kahn's topological sort over the rules that mention only pages of `update`.
+fn create_valid_path(
+    update: &[u32],
+    rules: &HashMap<u32, Vec<u32>>,
+) -> Result<Ordering, OrderError> {
+    let mut parents = HashMap::<u32, Vec<u32>>::new();
+    let mut children = HashMap::<u32, Vec<u32>>::new();
+    for number in update {
+        let relevant = rules
+            .get(number)
+            .into_iter()
+            .flatten()
+            .filter(|parent| update.contains(parent));
+        for parent in relevant {
+            parents.entry(*number).or_default().push(*parent);
+            children.entry(*parent).or_default().push(*number);
         }
     }
-    // root has no parent
-    let root = update.iter().filter(|x| !child_to_parent.contains_key(x)).next().unwrap();
+    let mut in_degree: HashMap<u32, usize> = update
+        .iter()
+        .map(|number| (*number, parents.get(number).map_or(0, |p| p.len())))
+        .collect();
 
-    let mut stack: Vec<(u32, Vec<u32>)> = vec![(*root, vec![*root])];
-    while !stack.is_empty() {
-        let (parent, path) = stack.pop().unwrap();
-        if path.len() == update.len() {
-            return path;
+    let mut ready: VecDeque<u32> = update
+        .iter()
+        .filter(|number| in_degree[number] == 0)
+        .copied()
+        .collect();
+    let mut pages = Vec::new();
+    let mut ambiguous = None;
+    while let Some(page) = ready.pop_front() {
+        if !ready.is_empty() && ambiguous.is_none() {
+            ambiguous = Some(std::iter::once(page).chain(ready.iter().copied()).collect());
         }
-        if let Some(children) = &parent_to_child.get(&parent) {
-            for child in *children {
-                let mut new_path = path.clone();
-                new_path.push(*child);
-                stack.push((*child, new_path));
+        pages.push(page);
+        for child in children.get(&page).into_iter().flatten() {
+            let degree = in_degree.get_mut(child).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push_back(*child);
             }
         }
     }
-    panic!("never should happen!")
+
+    if pages.len() < update.len() {
+        let remaining: HashSet<u32> = update
+            .iter()
+            .filter(|number| in_degree[number] > 0)
+            .copied()
+            .collect();
+        let start = *update
+            .iter()
+            .find(|number| remaining.contains(number))
+            .unwrap();
+        return Err(OrderError::Cycle(find_cycle(&remaining, &parents, start)));
+    }
+    Ok(Ordering { pages, ambiguous })
 }
 
-fn solution_b(updates: Vec<Vec<u32>>, rules: HashMap<u32, Vec<u32>>) -> u32 {
+fn solution_b(updates: Vec<Vec<u32>>, rules: HashMap<u32, Vec<u32>>) -> Result<u32, OrderError> {
     let mut res = 0;
     for update in updates {
         if validate_update(&update, &rules).is_none() {
-            let new_update = create_valid_path(&update, &rules);
-            if let Some(mid) = validate_update(&new_update, &rules) {
-                res += mid;
+            let ordering = create_valid_path(&update, &rules)?;
+            if let Some(pages) = ordering.ambiguous {
+                return Err(OrderError::Ambiguous(pages));
+            }
+            res += ordering.pages[ordering.pages.len() / 2];
+        }
+    }
+    Ok(res)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub page: u32,
+    /// Index of the page before the move.
+    pub from: usize,
+    /// Index of the page after the move.
+    pub to: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub moves: Vec<Move>,
+    pub pages: Vec<u32>,
+}
+
+/// `before[i][j]` when the rules, followed transitively through pages of `update`, put
+/// `update[i]` before `update[j]`.
+fn precedence_closure(update: &[u32], rules: &HashMap<u32, Vec<u32>>) -> Vec<Vec<bool>> {
+    let mut before = vec![vec![false; update.len()]; update.len()];
+    for (j, page) in update.iter().enumerate() {
+        for parent in rules.get(page).into_iter().flatten() {
+            if let Some(i) = update.iter().position(|p| p == parent) {
+                before[i][j] = true;
+            }
+        }
+    }
+    for k in 0..update.len() {
+        for i in 0..update.len() {
+            if before[i][k] {
+                let implied = before[k].clone();
+                for (cell, implied) in before[i].iter_mut().zip(implied) {
+                    *cell |= implied;
+                }
+            }
+        }
+    }
+    before
+}
+
+/// A largest set of vertices no two of which are related by `less`, which must be a strict
+/// partial order. By Dilworth and König this is every vertex whose left copy an alternating
+/// path from an unmatched left vertex reaches, but whose right copy it does not, in the
+/// bipartite graph of `less` under a maximum matching.
+fn maximum_antichain(less: &[Vec<bool>]) -> Vec<usize> {
+    fn augment(
+        x: usize,
+        less: &[Vec<bool>],
+        visited: &mut [bool],
+        matched_left: &mut [Option<usize>],
+    ) -> bool {
+        for y in 0..less.len() {
+            if less[x][y] && !visited[y] {
+                visited[y] = true;
+                if matched_left[y].is_none_or(|other| augment(other, less, visited, matched_left)) {
+                    matched_left[y] = Some(x);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    let n = less.len();
+    let mut matched_left = vec![None; n];
+    for x in 0..n {
+        augment(x, less, &mut vec![false; n], &mut matched_left);
+    }
+    let mut left_reached = (0..n)
+        .map(|x| !matched_left.contains(&Some(x)))
+        .collect_vec();
+    let mut right_reached = vec![false; n];
+    let mut stack = (0..n).filter(|x| left_reached[*x]).collect_vec();
+    while let Some(x) = stack.pop() {
+        for y in 0..n {
+            if less[x][y] && !right_reached[y] {
+                right_reached[y] = true;
+                if let Some(next) = matched_left[y].filter(|next| !left_reached[*next]) {
+                    left_reached[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+    }
+    (0..n)
+        .filter(|x| left_reached[*x] && !right_reached[*x])
+        .collect()
+}
+
+/// Moves the fewest pages needed to make `update` valid: every page outside a longest
+/// subsequence that already follows the rules, including the ones they imply through other
+/// pages of the update, moves once. Two pages break that order exactly when they are out of
+/// place with respect to each other, which is itself a partial order, so the longest such
+/// subsequence is a maximum antichain of it. The rules only need to be free of cycles, not
+/// to fix a unique order.
+pub fn repair_update(update: &[u32], rules: &HashMap<u32, Vec<u32>>) -> Result<Repair, OrderError> {
+    let target = create_valid_path(update, rules)?.pages;
+    let before = precedence_closure(update, rules);
+    let out_of_place = (0..update.len())
+        .map(|i| (0..update.len()).map(|j| before[i][j] && i > j).collect())
+        .collect_vec();
+    let mut placed: HashSet<u32> = maximum_antichain(&out_of_place)
+        .into_iter()
+        .map(|i| update[i])
+        .collect();
+    let index: HashMap<u32, usize> = update.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+    let must_precede = |a: &u32, b: &u32| before[index[a]][index[b]];
+
+    let mut pages = update.to_vec();
+    let mut moves = Vec::new();
+    for page in &target {
+        if placed.contains(page) {
+            continue;
+        }
+        let from = pages.iter().position(|p| p == page).unwrap();
+        pages.remove(from);
+        // after every placed page it needs to follow, which all come before any placed page
+        // it needs to precede
+        let to = match pages
+            .iter()
+            .rposition(|p| placed.contains(p) && must_precede(p, page))
+        {
+            Some(before) => before + 1,
+            None => pages.iter().position(|p| placed.contains(p)).unwrap_or(0),
+        };
+        pages.insert(to, *page);
+        placed.insert(*page);
+        moves.push(Move {
+            page: *page,
+            from,
+            to,
+        });
+    }
+    Ok(Repair { moves, pages })
+}
+
+/// The outcome of repairing every invalid update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairSummary {
+    /// The part 2 middle-page sum over the repaired updates.
+    pub middle_sum: u32,
+    /// The total number of moves needed for the repairs.
+    pub moves: usize,
+    /// Updates that could not be repaired, left out of the sums.
+    pub unrepairable: Vec<(Vec<u32>, OrderError)>,
+}
+
+pub fn solution_b_repair(updates: Vec<Vec<u32>>, rules: HashMap<u32, Vec<u32>>) -> RepairSummary {
+    let mut summary = RepairSummary {
+        middle_sum: 0,
+        moves: 0,
+        unrepairable: Vec::new(),
+    };
+    for update in updates {
+        if validate_update(&update, &rules).is_none() {
+            match repair_update(&update, &rules) {
+                Ok(repair) => {
+                    summary.middle_sum += repair.pages[repair.pages.len() / 2];
+                    summary.moves += repair.moves.len();
+                }
+                Err(error) => summary.unrepairable.push((update, error)),
+            }
+        }
+    }
+    summary
+}
+
+/// The rules as a directed graph with an edge `a -> b` for every rule `a|b`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleGraph {
+    children: BTreeMap<u32, BTreeSet<u32>>,
+}
+
+impl RuleGraph {
+    pub fn new(rules: &HashMap<u32, Vec<u32>>) -> RuleGraph {
+        let mut children = BTreeMap::<u32, BTreeSet<u32>>::new();
+        for (child, parents) in rules {
+            children.entry(*child).or_default();
+            for parent in parents {
+                children.entry(*parent).or_default().insert(*child);
+            }
+        }
+        RuleGraph { children }
+    }
+
+    pub fn pages(&self) -> impl Iterator<Item = u32> + '_ {
+        self.children.keys().copied()
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.children
+            .iter()
+            .flat_map(|(parent, children)| children.iter().map(|child| (*parent, *child)))
+    }
+
+    /// The rules that mention only `pages`.
+    pub fn induced(&self, pages: &[u32]) -> RuleGraph {
+        let children = pages
+            .iter()
+            .map(|page| {
+                let children = self.children.get(page).into_iter().flatten();
+                let children = children.filter(|child| pages.contains(child)).copied();
+                (*page, children.collect())
+            })
+            .collect();
+        RuleGraph { children }
+    }
+
+    /// Tarjan's algorithm; components come out in reverse topological order.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<u32>> {
+        #[derive(Default)]
+        struct Tarjan {
+            index: HashMap<u32, usize>,
+            low: HashMap<u32, usize>,
+            stack: Vec<u32>,
+            on_stack: HashSet<u32>,
+            components: Vec<Vec<u32>>,
+        }
+
+        fn visit(graph: &RuleGraph, state: &mut Tarjan, page: u32) {
+            let index = state.index.len();
+            state.index.insert(page, index);
+            state.low.insert(page, index);
+            state.stack.push(page);
+            state.on_stack.insert(page);
+            for &child in &graph.children[&page] {
+                if !state.index.contains_key(&child) {
+                    visit(graph, state, child);
+                    let low = state.low[&page].min(state.low[&child]);
+                    state.low.insert(page, low);
+                } else if state.on_stack.contains(&child) {
+                    let low = state.low[&page].min(state.index[&child]);
+                    state.low.insert(page, low);
+                }
+            }
+            if state.low[&page] == index {
+                let mut component = Vec::new();
+                while let Some(member) = state.stack.pop() {
+                    state.on_stack.remove(&member);
+                    component.push(member);
+                    if member == page {
+                        break;
+                    }
+                }
+                component.sort();
+                state.components.push(component);
+            }
+        }
+
+        let mut state = Tarjan::default();
+        for page in self.pages() {
+            if !state.index.contains_key(&page) {
+                visit(self, &mut state, page);
+            }
+        }
+        state.components
+    }
+
+    /// Rules between different components that other rules already imply. Rules inside a
+    /// cycle are never reported, since the transitive reduction is only defined on the
+    /// condensation.
+    pub fn redundant_rules(&self) -> Vec<(u32, u32)> {
+        let components = self.strongly_connected_components();
+        let component_of: HashMap<u32, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, component)| component.iter().map(move |page| (*page, i)))
+            .collect();
+        let mut successors = vec![BTreeSet::new(); components.len()];
+        for (parent, child) in self.rules() {
+            let (from, to) = (component_of[&parent], component_of[&child]);
+            if from != to {
+                successors[from].insert(to);
+            }
+        }
+        // successors always come earlier in Tarjan's output
+        let mut reachable = vec![HashSet::new(); components.len()];
+        for i in 0..components.len() {
+            let mut reach = HashSet::from([i]);
+            for &next in &successors[i] {
+                reach.extend(reachable[next].iter().copied());
+            }
+            reachable[i] = reach;
+        }
+        self.rules()
+            .filter(|(parent, child)| {
+                let (from, to) = (component_of[parent], component_of[child]);
+                from != to
+                    && successors[from]
+                        .iter()
+                        .any(|&next| next != to && reachable[next].contains(&to))
+            })
+            .collect()
+    }
+
+    /// Graphviz DOT source. With an `update` only the rules between its pages are drawn,
+    /// pages are labelled with their position and rules the update breaks are red.
+    pub fn to_dot(&self, update: Option<&[u32]>) -> String {
+        let graph = update.map_or(self.clone(), |update| self.induced(update));
+        let position = |page: u32| update.and_then(|update| update.iter().position(|p| *p == page));
+        let redundant: HashSet<(u32, u32)> = graph.redundant_rules().into_iter().collect();
+        let mut dot = String::from("digraph rules {\n");
+        for page in graph.pages() {
+            if let Some(i) = position(page) {
+                dot += &format!("    {} [label=\"{} (#{})\"];\n", page, page, i);
+            } else {
+                dot += &format!("    {};\n", page);
+            }
+        }
+        for (parent, child) in graph.rules() {
+            let mut attributes = Vec::new();
+            if position(parent) > position(child) {
+                attributes.push("color=red");
+            }
+            if redundant.contains(&(parent, child)) {
+                attributes.push("style=dashed");
+            }
+            if attributes.is_empty() {
+                dot += &format!("    {} -> {};\n", parent, child);
             } else {
-                panic!("never should happen!");
+                dot += &format!("    {} -> {} [{}];\n", parent, child, attributes.join(", "));
             }
         }
+        dot += "}\n";
+        dot
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleAnalysis {
+    /// Strongly connected components with more than one page.
+    pub cycles: Vec<Vec<u32>>,
+    pub redundant: Vec<(u32, u32)>,
+    /// Pages that appear in updates but in no rule.
+    pub unconstrained: Vec<u32>,
+}
+
+pub fn analyse_rules(updates: &[Vec<u32>], rules: &HashMap<u32, Vec<u32>>) -> RuleAnalysis {
+    let graph = RuleGraph::new(rules);
+    let cycles = graph
+        .strongly_connected_components()
+        .into_iter()
+        .filter(|component| component.len() > 1)
+        .collect();
+    let ruled: HashSet<u32> = graph.pages().collect();
+    let unconstrained = updates
+        .iter()
+        .flatten()
+        .filter(|page| !ruled.contains(page))
+        .copied()
+        .sorted()
+        .dedup()
+        .collect();
+    RuleAnalysis {
+        cycles,
+        redundant: graph.redundant_rules(),
+        unconstrained,
     }
-    res
 }
 
 #[cfg(test)]
@@ -133,24 +590,203 @@ mod tests {
         let (updates, rules) = parse(TEST_RULES, TEST_UPDATES);
         assert_eq!(solution_a(updates, rules), 143);
 
-        let (updates, rules) = parse(
-            RULES.replace("\r\n", "\n").as_str(),
-            UPDATES.replace("\r\n", "\n").as_str(),
-        );
+        let (updates, rules) = parse(RULES, UPDATES);
         println!("{}", solution_a(updates, rules));
 
         let (updates, rules) = parse(TEST_RULES, TEST_UPDATES);
-        assert_eq!(solution_b(updates, rules), 123);
-        let (updates, rules) = parse(
-            RULES.replace("\r\n", "\n").as_str(),
-            UPDATES.replace("\r\n", "\n").as_str(),
-        );
+        assert_eq!(solution_b(updates, rules), Ok(123));
+        let (updates, rules) = parse(RULES, UPDATES);
 
         let now = Instant::now();
-        println!("{}", solution_b(updates, rules));
+        println!("{}", solution_b(updates, rules).unwrap());
         println!("{:.2?}", now.elapsed());
     }
 
+    #[test]
+    fn topological_order() {
+        let (updates, rules) = parse(TEST_RULES, TEST_UPDATES);
+        let ordering = create_valid_path(&[97, 13, 75, 29, 47], &rules).unwrap();
+        assert_eq!(ordering.pages, vec![97, 75, 47, 29, 13]);
+        assert!(ordering.is_unique());
+
+        let ordering = create_valid_path(&[97, 13, 61], &rules).unwrap();
+        assert_eq!(ordering.pages, vec![97, 61, 13]);
+        let ordering = create_valid_path(&[53, 75, 47], &rules).unwrap();
+        assert!(ordering.is_unique());
+
+        let rules = to_rule_map(parse_rules("1|2\n2|3").unwrap().1);
+        let ordering = create_valid_path(&[3, 4, 1, 2], &rules).unwrap();
+        assert_eq!(ordering.pages, vec![4, 1, 2, 3]);
+        assert_eq!(ordering.ambiguous, Some(vec![4, 1]));
+        assert_eq!(
+            solution_b(vec![vec![3, 4, 1, 2]], rules),
+            Err(OrderError::Ambiguous(vec![4, 1]))
+        );
+
+        let rules = to_rule_map(parse_rules("1|2\n2|3\n3|1\n4|1").unwrap().1);
+        assert_eq!(
+            create_valid_path(&[4, 2, 3, 1], &rules),
+            Err(OrderError::Cycle(vec![3, 1, 2]))
+        );
+    }
+
+    #[test]
+    fn rule_analysis() {
+        let (updates, rules) = parse(TEST_RULES, TEST_UPDATES);
+        let analysis = analyse_rules(&updates, &rules);
+        assert!(analysis.cycles.is_empty());
+        assert!(analysis.unconstrained.is_empty());
+        assert_eq!(analysis.redundant.len(), 21 - 6);
+        assert!(!analysis.redundant.contains(&(97, 75)));
+        assert!(analysis.redundant.contains(&(97, 13)));
+
+        let graph = RuleGraph::new(&rules);
+        let dot = graph.to_dot(Some(&[61, 13, 29]));
+        assert!(dot.contains("13 [label=\"13 (#1)\"];"));
+        assert!(dot.contains("29 -> 13 [color=red];"));
+        assert!(dot.contains("61 -> 13 [style=dashed];"));
+        assert!(dot.contains("61 -> 29;"));
+        assert_eq!(dot.matches("->").count(), 3);
+
+        let rules = to_rule_map(parse_rules("1|2\n2|3\n3|1\n3|4\n4|5\n3|5").unwrap().1);
+        let analysis = analyse_rules(&[vec![1, 5, 6]], &rules);
+        assert_eq!(analysis.cycles, vec![vec![1, 2, 3]]);
+        assert_eq!(analysis.redundant, vec![(3, 5)]);
+        assert_eq!(analysis.unconstrained, vec![6]);
+
+        let (updates, rules) = parse(RULES, UPDATES);
+        let analysis = analyse_rules(&updates, &rules);
+        println!(
+            "{:?} {} {:?}",
+            analysis.cycles.iter().map(|c| c.len()).collect_vec(),
+            analysis.redundant.len(),
+            analysis.unconstrained
+        );
+    }
+
+    #[test]
+    fn minimal_repair() {
+        let (updates, rules) = parse(TEST_RULES, TEST_UPDATES);
+        let repair = repair_update(&[97, 13, 75, 29, 47], &rules).unwrap();
+        assert_eq!(repair.pages, vec![97, 75, 47, 29, 13]);
+        assert_eq!(
+            repair.moves,
+            vec![
+                Move {
+                    page: 47,
+                    from: 4,
+                    to: 3
+                },
+                Move {
+                    page: 13,
+                    from: 1,
+                    to: 4
+                },
+            ]
+        );
+        let repair = repair_update(&[75, 97, 47, 61, 53], &rules).unwrap();
+        assert_eq!(repair.moves.len(), 1);
+        assert_eq!(repair.pages, vec![97, 75, 47, 61, 53]);
+        let repair = repair_update(&[13, 29, 53, 61, 47, 75, 97], &rules).unwrap();
+        assert_eq!(repair.moves.len(), 6);
+        assert!(
+            repair_update(&[75, 47, 61], &rules)
+                .unwrap()
+                .moves
+                .is_empty()
+        );
+
+        assert_eq!(
+            solution_b_repair(updates, rules),
+            RepairSummary {
+                middle_sum: 123,
+                moves: 4,
+                unrepairable: vec![],
+            }
+        );
+
+        // 1|2 leaves 3 free, so there is no unique order, but moving 1 is enough
+        let rules = HashMap::from([(2, vec![1])]);
+        let repair = repair_update(&[2, 1, 3], &rules).unwrap();
+        assert_eq!(repair.moves.len(), 1);
+        assert_eq!(
+            validate_update(&repair.pages, &rules),
+            Some(repair.pages[1])
+        );
+        // 1|2 and 2|3 also put 1 before 3, so 3, 1 can't both stay in place and 3 moves
+        let rules = HashMap::from([(2, vec![1]), (3, vec![2])]);
+        let repair = repair_update(&[3, 1, 2], &rules).unwrap();
+        assert_eq!(
+            repair.moves,
+            vec![Move {
+                page: 3,
+                from: 0,
+                to: 2
+            }]
+        );
+        // but only through pages of the update
+        assert!(repair_update(&[3, 4, 1], &rules).unwrap().moves.is_empty());
+        let repair = repair_update(&[3, 2, 1], &rules).unwrap();
+        assert_eq!(repair.pages, vec![1, 2, 3]);
+        assert_eq!(repair.moves.len(), 2);
+        // a cycle only skips its own update
+        let rules = HashMap::from([(2, vec![1]), (1, vec![2]), (4, vec![3])]);
+        let summary = solution_b_repair(vec![vec![4, 3, 5], vec![2, 1]], rules);
+        assert_eq!(summary.middle_sum, 4);
+        assert_eq!(summary.moves, 1);
+        assert_eq!(
+            summary.unrepairable,
+            vec![(vec![2, 1], OrderError::Cycle(vec![1, 2]))]
+        );
+
+        let (updates, rules) = parse(RULES, UPDATES);
+        let summary = solution_b_repair(updates.clone(), rules.clone());
+        assert!(summary.unrepairable.is_empty());
+        assert_eq!(Ok(summary.middle_sum), solution_b(updates, rules));
+        println!("{} {}", summary.middle_sum, summary.moves);
+    }
+
+    #[test]
+    fn single_file_input() {
+        let input = format!("{}\n\n{}\n", TEST_RULES, TEST_UPDATES);
+        assert_eq!(parse_input(&input), parse(TEST_RULES, TEST_UPDATES));
+        assert_eq!(
+            parse_input(&input.replace("\n", "\r\n")),
+            parse(TEST_RULES, TEST_UPDATES)
+        );
+        let (updates, rules) = parse_input(&input.replace("\n", "\r\n"));
+        assert_eq!(solution_a(updates, rules), 143);
+
+        let input = format!("{}\n{}", RULES, UPDATES);
+        assert_eq!(parse_input(&input), parse(RULES, UPDATES));
+        assert_eq!(
+            parse_input(&input.replace("\n", "\r\n")),
+            parse(RULES, UPDATES)
+        );
+    }
+
+    #[test]
+    fn explain_violations() {
+        let (updates, rules) = parse(TEST_RULES, TEST_UPDATES);
+        assert!(explain_update(&updates[0], &rules).is_empty());
+        assert_eq!(
+            explain_update(&[61, 13, 29], &rules),
+            vec![RuleViolation {
+                before: 29,
+                after: 13,
+                before_position: 2,
+                after_position: 1,
+            }]
+        );
+        assert_eq!(explain_update(&[97, 13, 75, 29, 47], &rules).len(), 4);
+        for update in &updates {
+            assert_eq!(
+                explain_update(update, &rules).is_empty(),
+                validate_update(update, &rules).is_some()
+            );
+        }
+    }
+
     const TEST_UPDATES: &str = "75,47,61,53,29
 97,61,53,29,13
 75,29,13
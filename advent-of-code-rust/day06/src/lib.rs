@@ -10,20 +10,110 @@ enum Direction {
     Right,
 }
 
+impl Direction {
+    fn index(&self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        }
+    }
+
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
-struct State {
+pub struct State {
     x: usize,
     y: usize,
     dir: Direction,
 }
 
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+/// Which way guards turn at obstacles; `Alternating` cycles through its turns, counted per
+/// guard.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TurnPolicy {
+    Right,
+    Left,
+    Alternating(TurnSequence),
+}
+
+/// A non-empty sequence of turns, built with `TurnPolicy::alternating`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TurnSequence(Vec<Turn>);
+
+impl TurnPolicy {
+    /// `None` for an empty sequence, which would leave guards without a turn to take.
+    pub fn alternating(sequence: Vec<Turn>) -> Option<TurnPolicy> {
+        (!sequence.is_empty()).then_some(TurnPolicy::Alternating(TurnSequence(sequence)))
+    }
+
+    /// `right`, `left`, or a sequence of `R` and `L` such as `RRL`.
+    pub fn parse(text: &str) -> Option<TurnPolicy> {
+        match text {
+            "right" => Some(TurnPolicy::Right),
+            "left" => Some(TurnPolicy::Left),
+            _ => {
+                let sequence = text
+                    .chars()
+                    .map(|c| match c {
+                        'R' => Some(Turn::Right),
+                        'L' => Some(Turn::Left),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                TurnPolicy::alternating(sequence)
+            }
+        }
+    }
+
+    pub fn turn(&self, turns_taken: usize) -> Turn {
+        match self {
+            TurnPolicy::Right => Turn::Right,
+            TurnPolicy::Left => Turn::Left,
+            TurnPolicy::Alternating(TurnSequence(sequence)) => {
+                sequence[turns_taken % sequence.len()]
+            }
+        }
+    }
+
+    fn period(&self) -> usize {
+        match self {
+            TurnPolicy::Alternating(TurnSequence(sequence)) => sequence.len(),
+            _ => 1,
+        }
+    }
+}
+
 impl State {
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
     fn rotate(&self) -> State {
-        let new_direction = match self.dir {
-            Direction::Up => Direction::Right,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-            Direction::Right => Direction::Down,
+        self.turn(Turn::Right)
+    }
+
+    fn turn(&self, turn: Turn) -> State {
+        let new_direction = match (&self.dir, turn) {
+            (Direction::Up, Turn::Right) | (Direction::Down, Turn::Left) => Direction::Right,
+            (Direction::Down, Turn::Right) | (Direction::Up, Turn::Left) => Direction::Left,
+            (Direction::Left, Turn::Right) | (Direction::Right, Turn::Left) => Direction::Up,
+            (Direction::Right, Turn::Right) | (Direction::Left, Turn::Left) => Direction::Down,
         };
         State {
             x: self.x,
@@ -36,21 +126,31 @@ impl State {
         &self,
         obstacles: &[Vec<bool>],
         new_obstacle: Option<(usize, usize)>,
+    ) -> Option<State> {
+        self.advance_turning(obstacles, new_obstacle, Turn::Right)
+    }
+
+    fn advance_turning(
+        &self,
+        obstacles: &[Vec<bool>],
+        new_obstacle: Option<(usize, usize)>,
+        turn: Turn,
     ) -> Option<State> {
         let next_pos = State::get_next_pos(self, obstacles);
 
         if let Some((next_x, next_y)) = next_pos {
-            let new_state =
-                if obstacles[next_y][next_x] || new_obstacle.is_some_and(|x| (next_x, next_y) == x) {
-                    self.rotate()
-                } else {
-                    // travel
-                    State {
-                        x: next_x,
-                        y: next_y,
-                        dir: self.dir.clone(),
-                    }
-                };
+            let new_state = if obstacles[next_y][next_x]
+                || new_obstacle.is_some_and(|x| (next_x, next_y) == x)
+            {
+                self.turn(turn)
+            } else {
+                // travel
+                State {
+                    x: next_x,
+                    y: next_y,
+                    dir: self.dir.clone(),
+                }
+            };
             Some(new_state)
         } else {
             None
@@ -58,7 +158,7 @@ impl State {
     }
 
     fn get_next_pos(state: &State, obstacles: &[Vec<bool>]) -> Option<(usize, usize)> {
-        let next_pos = match state.dir {
+        match state.dir {
             Direction::Up => {
                 if state.y == 0 {
                     None
@@ -87,18 +187,22 @@ impl State {
                     Some((state.x + 1, state.y))
                 }
             }
-        };
-        next_pos
+        }
     }
+}
 
+pub fn parse_grid(input: &str) -> (Vec<Vec<bool>>, State) {
+    let (obstacles, guards) = parse_guards(input);
+    (obstacles, guards[0].clone())
 }
 
-fn parse_grid(input: &str) -> (Vec<Vec<bool>>, State) {
+/// Like `parse_grid`, but keeps every guard marker in reading order.
+pub fn parse_guards(input: &str) -> (Vec<Vec<bool>>, Vec<State>) {
     let obstacles = input
         .lines()
         .map(|line| line.chars().map(|c| c == '#').collect())
         .collect();
-    let pos = input
+    let guards = input
         .lines()
         .enumerate()
         .flat_map(|(y, line)| {
@@ -126,12 +230,12 @@ fn parse_grid(input: &str) -> (Vec<Vec<bool>>, State) {
                 _ => None,
             })
         })
-        .filter_map(|x| x)
-        .next();
-    (obstacles, pos.unwrap())
+        .flatten()
+        .collect();
+    (obstacles, guards)
 }
 
-fn solution_a(obstacles: &[Vec<bool>], initial_state: State) -> usize {
+pub fn solution_a(obstacles: &[Vec<bool>], initial_state: State) -> usize {
     let visited_positions = get_visited_positions(initial_state, obstacles);
     visited_positions.len()
 }
@@ -150,47 +254,348 @@ fn get_visited_positions(initial_state: State, obstacles: &[Vec<bool>]) -> HashS
     visited_positions
 }
 
-fn solution_b(obstacles: &[Vec<bool>], initial_state: State) -> usize {
-    let mut loops = 0;
+pub fn solution_b(obstacles: &[Vec<bool>], initial_state: State) -> usize {
+    find_loop_obstacles(obstacles, &initial_state).len()
+}
+
+/// Every position where a single new obstacle traps the guard in a loop, in reading order.
+fn find_loop_obstacles(obstacles: &[Vec<bool>], initial_state: &State) -> Vec<(usize, usize)> {
     let mut visited_positions = get_visited_positions(initial_state.clone(), obstacles);
     visited_positions.remove(&(initial_state.x, initial_state.y));
-    for new_obstacle in visited_positions {
-        if (detect_cycle(&initial_state, new_obstacle, obstacles)) {
-            loops += 1;
+    let jump_table = JumpTable::new(obstacles);
+    visited_positions
+        .into_iter()
+        .filter(|new_obstacle| detect_cycle(initial_state, *new_obstacle, &jump_table))
+        .sorted_by_key(|(x, y)| (*y, *x))
+        .collect()
+}
+
+/// The directions the guard travels through each cell, stopping at the map edge or when the
+/// route starts repeating.
+fn trace_route(
+    initial_state: &State,
+    obstacles: &[Vec<bool>],
+    new_obstacle: Option<(usize, usize)>,
+) -> HashMap<(usize, usize), HashSet<Direction>> {
+    let mut route = HashMap::<(usize, usize), HashSet<Direction>>::new();
+    let mut seen = HashSet::new();
+    let mut state = initial_state.clone();
+    while seen.insert(state.clone()) {
+        route
+            .entry((state.x, state.y))
+            .or_default()
+            .insert(state.dir.clone());
+        match state.advance(obstacles, new_obstacle) {
+            Some(new_state) => state = new_state,
+            None => break,
         }
     }
-    loops
+    route
 }
 
-fn detect_cycle(
+fn render_route(
+    obstacles: &[Vec<bool>],
+    initial_state: &State,
+    route: &HashMap<(usize, usize), HashSet<Direction>>,
+    marked_obstacles: &[(usize, usize)],
+) -> String {
+    let mut rendered = String::new();
+    for (y, row) in obstacles.iter().enumerate() {
+        for (x, obstacle) in row.iter().enumerate() {
+            let directions = route.get(&(x, y));
+            let vertical = directions
+                .is_some_and(|d| d.contains(&Direction::Up) || d.contains(&Direction::Down));
+            let horizontal = directions
+                .is_some_and(|d| d.contains(&Direction::Left) || d.contains(&Direction::Right));
+            rendered.push(if *obstacle {
+                '#'
+            } else if marked_obstacles.contains(&(x, y)) {
+                'O'
+            } else if (x, y) == (initial_state.x, initial_state.y) {
+                match initial_state.dir {
+                    Direction::Up => '^',
+                    Direction::Down => 'v',
+                    Direction::Left => '<',
+                    Direction::Right => '>',
+                }
+            } else {
+                match (vertical, horizontal) {
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    (false, true) => '-',
+                    (false, false) => '.',
+                }
+            });
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+/// The guard's patrol with every obstacle position that would cause a loop marked 'O'.
+pub fn render_patrol(obstacles: &[Vec<bool>], initial_state: &State) -> String {
+    let route = trace_route(initial_state, obstacles, None);
+    let loop_obstacles = find_loop_obstacles(obstacles, initial_state);
+    render_route(obstacles, initial_state, &route, &loop_obstacles)
+}
+
+/// The looping route the guard takes once `new_obstacle` is placed.
+pub fn render_loop(
+    obstacles: &[Vec<bool>],
     initial_state: &State,
     new_obstacle: (usize, usize),
+) -> String {
+    let route = trace_route(initial_state, obstacles, Some(new_obstacle));
+    render_route(obstacles, initial_state, &route, &[new_obstacle])
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GuardReport {
+    pub start: State,
+    pub visited: usize,
+    pub cycle: bool,
+    pub collided: bool,
+}
+
+/// Moves all guards one step per tick until each has left the map or started repeating its
+/// route. With `stop_at_collision` the run ends at the first tick where two guards share a
+/// cell or swap cells; looping guards keep patrolling for this until the guards as a whole
+/// are back in a state they were in before, as no new collision can happen after that.
+pub fn simulate_guards(
     obstacles: &[Vec<bool>],
-) -> bool {
-    let mut slow_state = initial_state.clone();
-    let mut fast_state = initial_state.clone();
+    guards: &[State],
+    policy: &TurnPolicy,
+    stop_at_collision: bool,
+) -> Vec<GuardReport> {
+    let mut states: Vec<Option<State>> = guards.iter().cloned().map(Some).collect();
+    let mut turns_taken = vec![0; guards.len()];
+    let mut visited: Vec<HashSet<(usize, usize)>> = guards
+        .iter()
+        .map(|guard| HashSet::from([(guard.x, guard.y)]))
+        .collect();
+    let mut seen: Vec<HashSet<(State, usize)>> = guards
+        .iter()
+        .map(|guard| HashSet::from([(guard.clone(), 0)]))
+        .collect();
+    let mut cycle = vec![false; guards.len()];
+    let mut collided = vec![false; guards.len()];
+    let joint_state = |states: &[Option<State>], turns_taken: &[usize]| {
+        states
+            .iter()
+            .zip(turns_taken)
+            .map(|(state, turns)| state.clone().map(|state| (state, turns % policy.period())))
+            .collect_vec()
+    };
+    let mut seen_together = HashSet::from([joint_state(&states, &turns_taken)]);
+
     loop {
-        if let Some(new_state) = slow_state.advance(obstacles, Some(new_obstacle)) {
-            slow_state = new_state;
-        } else {
-            return false; // reached map end
+        let moving = |i: usize| states[i].is_some() && (stop_at_collision || !cycle[i]);
+        if !(0..guards.len()).any(moving) {
+            break;
+        }
+        let previous = states.clone();
+        for (i, slot) in states.iter_mut().enumerate() {
+            let Some(state) = slot else {
+                continue;
+            };
+            if cycle[i] && !stop_at_collision {
+                continue;
+            }
+            let turn = policy.turn(turns_taken[i]);
+            let Some(new_state) = state.advance_turning(obstacles, None, turn) else {
+                *slot = None;
+                continue;
+            };
+            if new_state.dir != state.dir {
+                turns_taken[i] += 1;
+            }
+            visited[i].insert((new_state.x, new_state.y));
+            if !seen[i].insert((new_state.clone(), turns_taken[i] % policy.period())) {
+                cycle[i] = true;
+            }
+            *slot = Some(new_state);
         }
 
-        if let Some(new_state) = fast_state.advance(obstacles, Some(new_obstacle)) {
-            fast_state = new_state;
-        } else {
-            return false; // reached map end
+        if stop_at_collision {
+            for (i, j) in (0..guards.len()).tuple_combinations() {
+                let (Some(a), Some(b)) = (&states[i], &states[j]) else {
+                    continue;
+                };
+                let same_cell = a.position() == b.position();
+                let swapped = match (&previous[i], &previous[j]) {
+                    (Some(was_a), Some(was_b)) => {
+                        was_a.position() != was_b.position()
+                            && a.position() == was_b.position()
+                            && b.position() == was_a.position()
+                    }
+                    _ => false,
+                };
+                if same_cell || swapped {
+                    collided[i] = true;
+                    collided[j] = true;
+                }
+            }
+            if collided.contains(&true) || !seen_together.insert(joint_state(&states, &turns_taken))
+            {
+                break;
+            }
         }
+    }
 
-        if let Some(new_state) = fast_state.advance(obstacles, Some(new_obstacle)) {
-            fast_state = new_state;
-        } else {
-            return false; // reached map end
+    (0..guards.len())
+        .map(|i| GuardReport {
+            start: guards[i].clone(),
+            visited: visited[i].len(),
+            cycle: cycle[i],
+            collided: collided[i],
+        })
+        .collect()
+}
+
+/// For every cell and direction, the cell the guard stops on in front of the next obstacle,
+/// or `None` if it walks off the map first.
+struct JumpTable {
+    width: usize,
+    stops: Vec<[Option<(usize, usize)>; 4]>,
+}
+
+impl JumpTable {
+    fn new(obstacles: &[Vec<bool>]) -> JumpTable {
+        let height = obstacles.len();
+        let width = obstacles[0].len();
+        let mut stops = vec![[None; 4]; width * height];
+        let index = |x: usize, y: usize| y * width + x;
+        for x in 0..width {
+            for y in 1..height {
+                stops[index(x, y)][Direction::Up.index()] = if obstacles[y - 1][x] {
+                    Some((x, y))
+                } else {
+                    stops[index(x, y - 1)][Direction::Up.index()]
+                };
+            }
+            for y in (0..height - 1).rev() {
+                stops[index(x, y)][Direction::Down.index()] = if obstacles[y + 1][x] {
+                    Some((x, y))
+                } else {
+                    stops[index(x, y + 1)][Direction::Down.index()]
+                };
+            }
         }
-        if slow_state == fast_state {
+        for y in 0..height {
+            for x in 1..width {
+                stops[index(x, y)][Direction::Left.index()] = if obstacles[y][x - 1] {
+                    Some((x, y))
+                } else {
+                    stops[index(x - 1, y)][Direction::Left.index()]
+                };
+            }
+            for x in (0..width - 1).rev() {
+                stops[index(x, y)][Direction::Right.index()] = if obstacles[y][x + 1] {
+                    Some((x, y))
+                } else {
+                    stops[index(x + 1, y)][Direction::Right.index()]
+                };
+            }
+        }
+        JumpTable { width, stops }
+    }
+
+    /// Walks straight until the next obstacle, `new_obstacle` included, and turns there.
+    fn jump(&self, state: &State, new_obstacle: Option<(usize, usize)>) -> Option<State> {
+        let stop = self.stops[state.y * self.width + state.x][state.dir.index()];
+        let distance = |(x, y): (usize, usize)| x.abs_diff(state.x) + y.abs_diff(state.y);
+        let (dx, dy) = state.dir.delta();
+        let overlay_stop = new_obstacle.and_then(|(ox, oy)| {
+            let ahead = if dx == 0 {
+                ox == state.x && (oy as isize - state.y as isize).signum() == dy
+            } else {
+                oy == state.y && (ox as isize - state.x as isize).signum() == dx
+            };
+            let blocks = ahead && stop.is_none_or(|stop| distance((ox, oy)) <= distance(stop));
+            blocks.then(|| (ox.wrapping_add_signed(-dx), oy.wrapping_add_signed(-dy)))
+        });
+        overlay_stop.or(stop).map(|(x, y)| {
+            State {
+                x,
+                y,
+                dir: state.dir.clone(),
+            }
+            .rotate()
+        })
+    }
+}
+
+fn detect_cycle(
+    initial_state: &State,
+    new_obstacle: (usize, usize),
+    jump_table: &JumpTable,
+) -> bool {
+    let mut turns = HashSet::new();
+    let mut state = initial_state.clone();
+    while let Some(new_state) = jump_table.jump(&state, Some(new_obstacle)) {
+        if !turns.insert(new_state.clone()) {
             return true;
         }
+        state = new_state;
     }
+    false // reached map end
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CycleInfo {
+    /// Steps taken before the guard first stands in a state of the loop; a turn counts as a step.
+    pub tail_steps: usize,
+    pub period_steps: usize,
+    pub period_turns: usize,
+    pub cells: HashSet<(usize, usize)>,
+}
+
+/// Steps the guard with `new_obstacle` in place and describes the loop it ends up in, if any.
+pub fn characterize_cycle(
+    initial_state: &State,
+    new_obstacle: (usize, usize),
+    obstacles: &[Vec<bool>],
+) -> Option<CycleInfo> {
+    let mut states = vec![initial_state.clone()];
+    let mut step_of = HashMap::from([(initial_state.clone(), 0)]);
+    loop {
+        let state = states
+            .last()
+            .unwrap()
+            .advance(obstacles, Some(new_obstacle))?;
+        if let Some(&tail_steps) = step_of.get(&state) {
+            let cycle = &states[tail_steps..];
+            let period_turns = cycle
+                .iter()
+                .chain(std::iter::once(&state))
+                .tuple_windows()
+                .filter(|(a, b)| a.dir != b.dir)
+                .count();
+            return Some(CycleInfo {
+                tail_steps,
+                period_steps: cycle.len(),
+                period_turns,
+                cells: cycle.iter().map(|state| (state.x, state.y)).collect(),
+            });
+        }
+        step_of.insert(state.clone(), states.len());
+        states.push(state);
+    }
+}
+
+/// The loop-causing obstacles with their loops, shortest loop first.
+pub fn loop_obstacles_by_period(
+    obstacles: &[Vec<bool>],
+    initial_state: &State,
+) -> Vec<((usize, usize), CycleInfo)> {
+    find_loop_obstacles(obstacles, initial_state)
+        .into_iter()
+        .map(|new_obstacle| {
+            let info = characterize_cycle(initial_state, new_obstacle, obstacles).unwrap();
+            (new_obstacle, info)
+        })
+        .sorted_by_key(|((x, y), info)| (info.period_steps, *y, *x))
+        .collect()
 }
 
 #[cfg(test)]
@@ -212,6 +617,188 @@ mod tests {
         println!("{}", solution_b(&obstacles, initial_state));
     }
 
+    /// The original Floyd cycle detection, stepping one cell at a time.
+    fn detect_cycle_stepwise(
+        initial_state: &State,
+        new_obstacle: (usize, usize),
+        obstacles: &[Vec<bool>],
+    ) -> bool {
+        let mut slow_state = initial_state.clone();
+        let mut fast_state = initial_state.clone();
+        loop {
+            if let Some(new_state) = slow_state.advance(obstacles, Some(new_obstacle)) {
+                slow_state = new_state;
+            } else {
+                return false;
+            }
+            for _ in 0..2 {
+                if let Some(new_state) = fast_state.advance(obstacles, Some(new_obstacle)) {
+                    fast_state = new_state;
+                } else {
+                    return false;
+                }
+            }
+            if slow_state == fast_state {
+                return true;
+            }
+        }
+    }
+
+    #[test]
+    fn jump_table_matches_stepping() {
+        for input in [TEST_INPUT, INPUT] {
+            let (obstacles, initial_state) = parse_grid(input);
+            let jump_table = JumpTable::new(&obstacles);
+            for (y, row) in obstacles.iter().enumerate().step_by(3) {
+                for (x, obstacle) in row.iter().enumerate() {
+                    if *obstacle || (x, y) == (initial_state.x, initial_state.y) {
+                        continue;
+                    }
+                    assert_eq!(
+                        detect_cycle(&initial_state, (x, y), &jump_table),
+                        detect_cycle_stepwise(&initial_state, (x, y), &obstacles),
+                        "{:?}",
+                        (x, y)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rendering() {
+        let (obstacles, initial_state) = parse_grid(TEST_INPUT);
+        assert_eq!(
+            render_loop(&obstacles, &initial_state, (3, 6)),
+            "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...
+"
+        );
+        let patrol = render_patrol(&obstacles, &initial_state);
+        assert_eq!(patrol.matches('O').count(), 6);
+        assert_eq!(patrol.matches(['|', '-', '+', 'O', '^']).count(), 41);
+        println!("{}", patrol);
+    }
+
+    #[test]
+    fn multiple_guards() {
+        let (obstacles, guards) = parse_guards(TEST_INPUT);
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, false);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].visited, 41);
+        assert!(!reports[0].cycle);
+
+        let (obstacles, guards) = parse_guards(INPUT);
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, false);
+        assert_eq!(
+            reports[0].visited,
+            solution_a(&obstacles, guards[0].clone())
+        );
+
+        let input = "\
+.#...
+>...#
+.^...
+#....
+...#.";
+        let (obstacles, guards) = parse_guards(input);
+        assert_eq!(guards.len(), 2);
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, false);
+        assert!(reports[0].cycle);
+        assert_eq!(reports[0].visited, 9);
+        assert!(reports[1].cycle);
+        assert_eq!(reports[1].visited, 8);
+
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, true);
+        assert!(reports.iter().all(|report| report.collided));
+
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Left, false);
+        assert!(reports.iter().all(|report| !report.cycle));
+        assert_eq!(reports[0].visited, 5);
+        assert_eq!(reports[1].visited, 3);
+
+        let alternating = TurnPolicy::alternating(vec![Turn::Right, Turn::Left]).unwrap();
+        assert_eq!(alternating.turn(3), Turn::Left);
+        assert_eq!(TurnPolicy::parse("RL"), Some(alternating.clone()));
+        assert_eq!(TurnPolicy::alternating(vec![]), None);
+        assert_eq!(TurnPolicy::parse(""), None);
+        assert_eq!(TurnPolicy::parse("RX"), None);
+        let reports = simulate_guards(&obstacles, &guards, &alternating, false);
+        assert!(reports.iter().all(|report| !report.cycle));
+        assert_eq!(reports[0].visited, 7);
+        assert_eq!(reports[1].visited, 5);
+
+        // the first guard is looping long before the second one walks into its loop
+        let input = "\
+.#.....
+.^...#.
+.......
+#......
+....#..
+.......
+.......
+.......
+.......
+.......
+.......
+.......
+.......
+.......
+.......
+.......
+.......
+..^....";
+        let (obstacles, guards) = parse_guards(input);
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, false);
+        assert!(reports[0].cycle);
+        assert!(reports.iter().all(|report| !report.collided));
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, true);
+        assert!(reports[0].cycle);
+        assert!(reports.iter().all(|report| report.collided));
+
+        // guards walking into each other swap cells without ever sharing one
+        let (obstacles, guards) = parse_guards(">..<");
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, true);
+        assert!(reports.iter().all(|report| report.collided));
+        let (obstacles, guards) = parse_guards(">...<");
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, true);
+        assert!(reports.iter().all(|report| report.collided));
+        let reports = simulate_guards(&obstacles, &guards, &TurnPolicy::Right, false);
+        assert!(reports.iter().all(|report| !report.collided));
+        assert_eq!(reports[0].visited, 5);
+    }
+
+    #[test]
+    fn cycle_characterization() {
+        let (obstacles, initial_state) = parse_grid(TEST_INPUT);
+        assert_eq!(characterize_cycle(&initial_state, (0, 0), &obstacles), None);
+
+        let info = characterize_cycle(&initial_state, (3, 6), &obstacles).unwrap();
+        assert_eq!(info.tail_steps, 0);
+        assert_eq!(info.period_steps, 5 + 4 + 5 + 4 + 4);
+        assert_eq!(info.period_turns, 4);
+        assert_eq!(info.cells.len(), 18);
+
+        let by_period = loop_obstacles_by_period(&obstacles, &initial_state);
+        assert_eq!(
+            by_period.len(),
+            solution_b(&obstacles, initial_state.clone())
+        );
+        assert!(by_period.is_sorted_by_key(|(_, info)| info.period_steps));
+        for (new_obstacle, info) in &by_period {
+            assert!(info.cells.len() <= info.period_steps);
+            assert!(!info.cells.contains(new_obstacle));
+        }
+    }
+
     const TEST_INPUT: &str = "....#.....
 .........#
 ..........
@@ -0,0 +1,96 @@
+use day06::{
+    TurnPolicy, characterize_cycle, loop_obstacles_by_period, parse_grid, parse_guards,
+    render_loop, render_patrol, simulate_guards, solution_a, solution_b,
+};
+use std::{env, fs};
+
+const INPUT: &str = include_str!("input.txt");
+
+fn parse_position(text: &str) -> (usize, usize) {
+    let (x, y) = text.split_once(',').expect("positions are written as x,y");
+    (x.parse().unwrap(), y.parse().unwrap())
+}
+
+fn main() {
+    let mut render = false;
+    let mut cycles = false;
+    let mut new_obstacle = None;
+    let mut guards = false;
+    let mut policy = TurnPolicy::Right;
+    let mut stop_at_collision = false;
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--render" => render = true,
+            "--cycles" => cycles = true,
+            "--loop" => new_obstacle = Some(parse_position(&args.next().unwrap())),
+            "--guards" => guards = true,
+            // --turn right|left|RRL: how the guards turn at obstacles (implies --guards)
+            "--turn" => {
+                let text = args.next().unwrap_or_default();
+                policy = TurnPolicy::parse(&text)
+                    .unwrap_or_else(|| panic!("unknown turn policy {:?}", text));
+                guards = true;
+            }
+            "--stop-at-collision" => {
+                stop_at_collision = true;
+                guards = true;
+            }
+            _ => path = Some(arg),
+        }
+    }
+    let input = path.map_or(INPUT.to_string(), |path| fs::read_to_string(path).unwrap());
+    let (obstacles, initial_state) = parse_grid(&input);
+
+    if guards {
+        let (obstacles, guards) = parse_guards(&input);
+        for report in simulate_guards(&obstacles, &guards, &policy, stop_at_collision) {
+            let (x, y) = report.start.position();
+            println!(
+                "{},{}: visited {}, {}{}",
+                x,
+                y,
+                report.visited,
+                if report.cycle { "loops" } else { "no loop" },
+                if report.collided { ", collided" } else { "" }
+            );
+        }
+        return;
+    }
+
+    if let Some(new_obstacle) = new_obstacle {
+        print!("{}", render_loop(&obstacles, &initial_state, new_obstacle));
+        match characterize_cycle(&initial_state, new_obstacle, &obstacles) {
+            Some(info) => println!(
+                "tail {} steps, period {} steps / {} turns over {} cells",
+                info.tail_steps,
+                info.period_steps,
+                info.period_turns,
+                info.cells.len()
+            ),
+            None => println!("no loop"),
+        }
+    } else if cycles {
+        println!(
+            "{:>4} {:>4} {:>6} {:>6} {:>6} {:>6}",
+            "x", "y", "tail", "period", "turns", "cells"
+        );
+        for ((x, y), info) in loop_obstacles_by_period(&obstacles, &initial_state) {
+            println!(
+                "{:>4} {:>4} {:>6} {:>6} {:>6} {:>6}",
+                x,
+                y,
+                info.tail_steps,
+                info.period_steps,
+                info.period_turns,
+                info.cells.len()
+            );
+        }
+    } else if render {
+        print!("{}", render_patrol(&obstacles, &initial_state));
+    } else {
+        println!("{}", solution_a(&obstacles, initial_state.clone()));
+        println!("{}", solution_b(&obstacles, initial_state));
+    }
+}
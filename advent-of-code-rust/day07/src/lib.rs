@@ -2,7 +2,7 @@
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
-fn parse_equations(input: &str) -> Vec<(i64, Vec<i64>)> {
+pub fn parse_equations(input: &str) -> Vec<(i128, Vec<i128>)> {
     input
         .lines()
         .map(|line| {
@@ -14,18 +14,15 @@ fn parse_equations(input: &str) -> Vec<(i64, Vec<i64>)> {
         .collect()
 }
 
-fn try_minus(answer: i64, number: i64) -> Option<i64> {
-    let minus_answer = answer - number;
-    let valid_minus = minus_answer > 0;
-    if valid_minus {
-        Some(minus_answer)
-    } else {
-        None
-    }
+fn try_minus(answer: i128, number: i128) -> Option<i128> {
+    answer.checked_sub(number)
 }
 
-fn try_divide(answer: i64, number: i64) -> Option<i64> {
-    let divide_answer = answer / number;
+fn try_divide(answer: i128, number: i128) -> Option<i128> {
+    if number == 0 {
+        return None;
+    }
+    let divide_answer = answer.checked_div(number)?;
     let valid_divide = answer % number == 0;
     if valid_divide {
         Some(divide_answer)
@@ -34,14 +31,19 @@ fn try_divide(answer: i64, number: i64) -> Option<i64> {
     }
 }
 
-fn try_split_tail(full: i64, tail: i64) -> Option<i64> {
-    // Calculate the length (number of digits) of the tail
-    let mut divisor = 1;
-    let mut temp = tail;
-    while temp > 0 {
-        divisor *= 10;
-        temp /= 10;
+/// `10^n` for the number of decimal digits `n` in `number`, or `None` if it overflows.
+fn digit_shift(number: i128) -> Option<i128> {
+    let digits = number.checked_ilog10().unwrap_or(0) + 1;
+    10i128.checked_pow(digits)
+}
+
+fn try_split_tail(full: i128, tail: i128) -> Option<i128> {
+    if full < 0 || tail < 0 {
+        return None;
     }
+    // Shift by the length (number of digits) of the tail; a tail longer than the
+    // largest `i128` cannot be the end of `full`
+    let divisor = digit_shift(tail)?;
 
     // Check if the tail matches the last digits of the full number
     if full % divisor == tail {
@@ -52,18 +54,374 @@ fn try_split_tail(full: i64, tail: i64) -> Option<i64> {
     }
 }
 
-fn is_valid(answer: i64, inputs: &[i64], i: usize) -> bool {
-    let minus_answer = try_minus(answer, inputs[i]);
-    let divide_answer = try_divide(answer, inputs[i]);
+/// A binary operator of the left-to-right calibration equations.
+pub trait Operator {
+    fn symbol(&self) -> &'static str;
+
+    /// Binding strength under standard precedence; higher binds tighter.
+    fn precedence(&self) -> u8;
+
+    /// Whether `left <op> right` is at least both operands when they are positive, so a
+    /// forward search may give up once a partial value exceeds the target.
+    fn is_monotone(&self) -> bool {
+        false
+    }
+
+    /// `left <op> right`, if it is defined.
+    fn apply(&self, left: i128, right: i128) -> Option<i128>;
+
+    /// The `left` for which `left <op> right == result`, if there is one.
+    fn try_invert(&self, result: i128, right: i128) -> Option<i128>;
+
+    /// Whether `left <op> right == result` for every `left`, as for `x * 0 == 0`, so the
+    /// inputs before `right` only need some defined evaluation.
+    fn absorbs(&self, result: i128, right: i128) -> bool {
+        false
+    }
+}
+
+pub struct Add;
+pub struct Mul;
+pub struct Concat;
+pub struct Sub;
+pub struct Xor;
+
+impl Operator for Add {
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
 
+    fn is_monotone(&self) -> bool {
+        true
+    }
+
+    fn apply(&self, left: i128, right: i128) -> Option<i128> {
+        left.checked_add(right)
+    }
+
+    fn try_invert(&self, result: i128, right: i128) -> Option<i128> {
+        try_minus(result, right)
+    }
+}
+
+impl Operator for Mul {
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    fn is_monotone(&self) -> bool {
+        true
+    }
+
+    fn apply(&self, left: i128, right: i128) -> Option<i128> {
+        left.checked_mul(right)
+    }
+
+    fn try_invert(&self, result: i128, right: i128) -> Option<i128> {
+        try_divide(result, right)
+    }
+
+    fn absorbs(&self, result: i128, right: i128) -> bool {
+        result == 0 && right == 0
+    }
+}
+
+impl Operator for Concat {
+    fn symbol(&self) -> &'static str {
+        "||"
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+
+    fn is_monotone(&self) -> bool {
+        true
+    }
+
+    fn apply(&self, left: i128, right: i128) -> Option<i128> {
+        if left < 0 || right < 0 {
+            return None;
+        }
+        left.checked_mul(digit_shift(right)?)?.checked_add(right)
+    }
+
+    fn try_invert(&self, result: i128, right: i128) -> Option<i128> {
+        try_split_tail(result, right)
+    }
+}
+
+impl Operator for Sub {
+    fn symbol(&self) -> &'static str {
+        "-"
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, left: i128, right: i128) -> Option<i128> {
+        left.checked_sub(right)
+    }
+
+    fn try_invert(&self, result: i128, right: i128) -> Option<i128> {
+        result.checked_add(right)
+    }
+}
+
+impl Operator for Xor {
+    fn symbol(&self) -> &'static str {
+        "^"
+    }
+
+    fn precedence(&self) -> u8 {
+        0
+    }
+
+    fn apply(&self, left: i128, right: i128) -> Option<i128> {
+        Some(left ^ right)
+    }
+
+    fn try_invert(&self, result: i128, right: i128) -> Option<i128> {
+        Some(result ^ right)
+    }
+}
+
+pub const PART_A: &[&dyn Operator] = &[&Add, &Mul];
+pub const PART_B: &[&dyn Operator] = &[&Add, &Mul, &Concat];
+
+/// The operators between `inputs[..=i]` that evaluate left to right to `answer`, found by
+/// undoing the last operator and searching backwards.
+pub fn find_witness<'a>(
+    answer: i128,
+    inputs: &[i128],
+    i: usize,
+    operators: &[&'a dyn Operator],
+) -> Option<Vec<&'a dyn Operator>> {
     if i == 0 {
-        return minus_answer.is_some_and(|x| x == 0) || divide_answer.is_some_and(|x| x == 1);
+        return (answer == inputs[0]).then(Vec::new);
     }
-    minus_answer.is_some_and(|x| is_valid(x, inputs, i - 1))
-        || divide_answer.is_some_and(|x| is_valid(x, inputs, i - 1))
+    operators.iter().find_map(|operator| {
+        if operator.absorbs(answer, inputs[i]) {
+            let mut ops = first_defined(&inputs[..i], operators)?;
+            ops.push(*operator);
+            return Some(ops);
+        }
+        let previous = operator.try_invert(answer, inputs[i])?;
+        let mut ops = find_witness(previous, inputs, i - 1, operators)?;
+        ops.push(*operator);
+        Some(ops)
+    })
+}
+
+/// The first operator assignment for `inputs` whose left to right evaluation does not
+/// overflow.
+fn first_defined<'a>(
+    inputs: &[i128],
+    operators: &[&'a dyn Operator],
+) -> Option<Vec<&'a dyn Operator>> {
+    fn search<'a>(
+        value: i128,
+        inputs: &[i128],
+        operators: &[&'a dyn Operator],
+        ops: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        let Some((&right, rest)) = inputs.split_first() else {
+            return true;
+        };
+        for operator in operators {
+            if let Some(next) = operator.apply(value, right) {
+                ops.push(*operator);
+                if search(next, rest, operators, ops) {
+                    return true;
+                }
+                ops.pop();
+            }
+        }
+        false
+    }
+
+    let mut ops = Vec::new();
+    search(inputs[0], &inputs[1..], operators, &mut ops).then_some(ops)
 }
 
-fn solution_a(equations: Vec<(i64, Vec<i64>)>) -> i64 {
+/// The number of operator assignments for `inputs` whose left to right evaluation does not
+/// overflow, or `None` if that does not fit in a `u128`.
+fn count_defined(inputs: &[i128], operators: &[&dyn Operator]) -> Option<u128> {
+    let mut values = HashMap::from([(inputs[0], 1u128)]);
+    for &right in &inputs[1..] {
+        let mut next: HashMap<i128, u128> = HashMap::new();
+        for (value, ways) in values {
+            for operator in operators {
+                if let Some(result) = operator.apply(value, right) {
+                    let entry = next.entry(result).or_default();
+                    *entry = entry.checked_add(ways)?;
+                }
+            }
+        }
+        values = next;
+    }
+    values
+        .values()
+        .try_fold(0u128, |total, ways| total.checked_add(*ways))
+}
+
+/// The number of operator assignments for `inputs` that evaluate to `answer`, or `None` if
+/// it does not fit in a `u128`.
+pub fn count_assignments(
+    answer: i128,
+    inputs: &[i128],
+    operators: &[&dyn Operator],
+) -> Option<u128> {
+    fn count(
+        answer: i128,
+        inputs: &[i128],
+        i: usize,
+        operators: &[&dyn Operator],
+        memo: &mut HashMap<(usize, i128), Option<u128>>,
+    ) -> Option<u128> {
+        if i == 0 {
+            return Some(u128::from(answer == inputs[0]));
+        }
+        if let Some(&known) = memo.get(&(i, answer)) {
+            return known;
+        }
+        let mut total: Option<u128> = Some(0);
+        for operator in operators {
+            if operator.absorbs(answer, inputs[i]) {
+                let ways = count_defined(&inputs[..i], operators);
+                total = total
+                    .zip(ways)
+                    .and_then(|(total, ways)| total.checked_add(ways));
+            } else if let Some(previous) = operator.try_invert(answer, inputs[i]) {
+                let ways = count(previous, inputs, i - 1, operators, memo);
+                total = total
+                    .zip(ways)
+                    .and_then(|(total, ways)| total.checked_add(ways));
+            }
+        }
+        memo.insert((i, answer), total);
+        total
+    }
+
+    count(
+        answer,
+        inputs,
+        inputs.len() - 1,
+        operators,
+        &mut HashMap::new(),
+    )
+}
+
+/// Pairs `operators` with their own binding strength: `^` below `+` and `-`, below `*`,
+/// below `||`. Callers wanting another order can build the pairs themselves.
+pub fn standard_precedence<'a>(operators: &[&'a dyn Operator]) -> Vec<(&'a dyn Operator, u8)> {
+    operators
+        .iter()
+        .map(|operator| (*operator, operator.precedence()))
+        .collect()
+}
+
+/// Like `find_witness`, but evaluating with operator precedence instead of left to right.
+/// Operators of equal precedence still associate to the left. Inverting the last operator
+/// no longer works here, so this searches forwards, keeping the not yet reducible operands
+/// on a stack and, when every input is positive and every operator monotone, pruning once
+/// the prefix evaluated so far exceeds `answer`.
+pub fn find_precedence_witness<'a>(
+    answer: i128,
+    inputs: &[i128],
+    operators: &[(&'a dyn Operator, u8)],
+) -> Option<Vec<&'a dyn Operator>> {
+    type Pending<'a> = (i128, &'a dyn Operator, u8);
+
+    struct Search<'s, 'a> {
+        answer: i128,
+        inputs: &'s [i128],
+        operators: &'s [(&'a dyn Operator, u8)],
+        prune: bool,
+        ops: Vec<&'a dyn Operator>,
+        stack: Vec<Pending<'a>>,
+        /// Entries `reduce` popped off `stack`, to put back when backtracking.
+        popped: Vec<Pending<'a>>,
+    }
+
+    /// Applies every pending operator binding at least as tightly as `precedence`.
+    fn reduce(state: &mut Search<'_, '_>, mut current: i128, precedence: u8) -> Option<i128> {
+        while let Some(&top) = state.stack.last().filter(|top| top.2 >= precedence) {
+            current = top.1.apply(top.0, current)?;
+            state.popped.push(state.stack.pop().unwrap());
+        }
+        Some(current)
+    }
+
+    fn search(state: &mut Search<'_, '_>, i: usize, current: i128) -> bool {
+        // every later operand replaces some subexpression `x` of this prefix with `x op y`,
+        // which for monotone operators never makes it smaller
+        let total = state
+            .stack
+            .iter()
+            .rev()
+            .try_fold(current, |right, (left, operator, _)| {
+                operator.apply(*left, right)
+            });
+        if i == state.inputs.len() {
+            return total == Some(state.answer);
+        }
+        if state.prune && total.is_none_or(|total| total > state.answer) {
+            return false;
+        }
+        for &(operator, precedence) in state.operators {
+            let mark = state.popped.len();
+            let found = match reduce(state, current, precedence) {
+                Some(reduced) => {
+                    state.stack.push((reduced, operator, precedence));
+                    state.ops.push(operator);
+                    let found = search(state, i + 1, state.inputs[i]);
+                    if !found {
+                        state.ops.pop();
+                        state.stack.pop();
+                    }
+                    found
+                }
+                None => false,
+            };
+            if found {
+                return true;
+            }
+            while state.popped.len() > mark {
+                let entry = state.popped.pop().unwrap();
+                state.stack.push(entry);
+            }
+        }
+        false
+    }
+
+    let mut state = Search {
+        answer,
+        inputs,
+        operators,
+        prune: inputs.iter().all(|input| *input > 0)
+            && operators.iter().all(|(operator, _)| operator.is_monotone()),
+        ops: Vec::new(),
+        stack: Vec::new(),
+        popped: Vec::new(),
+    };
+    search(&mut state, 1, inputs[0]).then_some(state.ops)
+}
+
+fn is_valid(answer: i128, inputs: &[i128], i: usize) -> bool {
+    find_witness(answer, inputs, i, PART_A).is_some()
+}
+
+fn solution_a(equations: Vec<(i128, Vec<i128>)>) -> i128 {
     equations
         .iter()
         .filter(|(answer, inputs)| is_valid(*answer, inputs, inputs.len() - 1))
@@ -71,23 +429,11 @@ fn solution_a(equations: Vec<(i64, Vec<i64>)>) -> i64 {
         .sum()
 }
 
-fn is_valid_part_b(answer: i64, inputs: &[i64], i: usize) -> bool {
-    let minus_answer = try_minus(answer, inputs[i]);
-    let divide_answer = try_divide(answer, inputs[i]);
-    let split_answer = try_split_tail(answer, inputs[i]);
-
-    if i == 0 {
-        return minus_answer.is_some_and(|x| x == 0)
-            || divide_answer.is_some_and(|x| x == 1)
-            || split_answer.is_some_and(|head| head == 0);
-    }
-
-    minus_answer.is_some_and(|x| is_valid_part_b(x, inputs, i - 1))
-        || divide_answer.is_some_and(|x| is_valid_part_b(x, inputs, i - 1))
-        || split_answer.is_some_and(|head| is_valid_part_b(head, inputs, i - 1))
+fn is_valid_part_b(answer: i128, inputs: &[i128], i: usize) -> bool {
+    find_witness(answer, inputs, i, PART_B).is_some()
 }
 
-fn solution_b(equations: Vec<(i64, Vec<i64>)>) -> i64 {
+fn solution_b(equations: Vec<(i128, Vec<i128>)>) -> i128 {
     equations
         .iter()
         .filter(|(answer, inputs)| is_valid_part_b(*answer, inputs, inputs.len() - 1))
@@ -95,6 +441,46 @@ fn solution_b(equations: Vec<(i64, Vec<i64>)>) -> i64 {
         .sum()
 }
 
+pub fn solution_precedence(
+    equations: &[(i128, Vec<i128>)],
+    operators: &[(&dyn Operator, u8)],
+) -> i128 {
+    equations
+        .iter()
+        .filter(|(answer, inputs)| find_precedence_witness(*answer, inputs, operators).is_some())
+        .map(|(answer, _)| answer)
+        .sum()
+}
+
+/// Folds `inputs` left to right with `ops`.
+pub fn evaluate(inputs: &[i128], ops: &[&dyn Operator]) -> Option<i128> {
+    ops.iter()
+        .zip(&inputs[1..])
+        .try_fold(inputs[0], |left, (op, right)| op.apply(left, *right))
+}
+
+pub fn format_witness(inputs: &[i128], ops: &[&dyn Operator]) -> String {
+    let mut expression = inputs[0].to_string();
+    for (op, input) in ops.iter().zip(&inputs[1..]) {
+        expression += &format!(" {} {}", op.symbol(), input);
+    }
+    expression
+}
+
+/// Every solvable equation with the operators that solve it.
+pub fn witnesses(
+    equations: &[(i128, Vec<i128>)],
+    operators: &[&dyn Operator],
+) -> Vec<(i128, String)> {
+    equations
+        .iter()
+        .filter_map(|(answer, inputs)| {
+            let ops = find_witness(*answer, inputs, inputs.len() - 1, operators)?;
+            Some((*answer, format_witness(inputs, &ops)))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,6 +500,175 @@ mod tests {
         println!("{}", solution_b(equations));
     }
 
+    #[test]
+    fn operator_witnesses() {
+        let equations = parse_equations(TEST_INPUT);
+        assert_eq!(
+            witnesses(&equations, PART_A),
+            vec![
+                (190, String::from("10 * 19")),
+                (3267, String::from("81 * 40 + 27")),
+                (292, String::from("11 + 6 * 16 + 20")),
+            ]
+        );
+        let witnesses_b = witnesses(&equations, PART_B);
+        assert_eq!(witnesses_b.len(), 6);
+        assert!(witnesses_b.contains(&(7290, String::from("6 * 8 || 6 * 15"))));
+        assert!(witnesses_b.contains(&(156, String::from("15 || 6"))));
+
+        let equations = parse_equations(INPUT);
+        let total_a: i128 = witnesses(&equations, PART_A)
+            .iter()
+            .map(|(answer, _)| answer)
+            .sum();
+        let total_b: i128 = witnesses(&equations, PART_B)
+            .iter()
+            .map(|(answer, _)| answer)
+            .sum();
+        assert_eq!(total_a, solution_a(equations.clone()));
+        assert_eq!(total_b, solution_b(equations));
+    }
+
+    #[test]
+    fn custom_operators() {
+        let operators: &[&dyn Operator] = &[&Add, &Sub, &Xor];
+        let ops = find_witness(7, &[10, 5, 2], 2, operators).unwrap();
+        assert_eq!(format_witness(&[10, 5, 2], &ops), "10 - 5 + 2");
+        assert_eq!(evaluate(&[10, 5, 2], &ops), Some(7));
+        let ops = find_witness(1, &[6, 3, 4], 2, &[&Add, &Xor]).unwrap();
+        assert_eq!(format_witness(&[6, 3, 4], &ops), "6 ^ 3 ^ 4");
+        assert!(find_witness(100, &[6, 3, 4], 2, operators).is_none());
+
+        for (answer, inputs) in parse_equations(TEST_INPUT) {
+            if let Some(ops) = find_witness(answer, &inputs, inputs.len() - 1, PART_B) {
+                assert_eq!(evaluate(&inputs, &ops), Some(answer));
+            }
+        }
+    }
+
+    #[test]
+    fn counting_assignments() {
+        let equations = parse_equations(TEST_INPUT);
+        let counts = equations
+            .iter()
+            .map(|(answer, inputs)| count_assignments(*answer, inputs, PART_A).unwrap())
+            .collect_vec();
+        assert_eq!(counts, vec![1, 2, 0, 0, 0, 0, 0, 0, 1]);
+        let counts = equations
+            .iter()
+            .map(|(answer, inputs)| count_assignments(*answer, inputs, PART_B).unwrap())
+            .collect_vec();
+        assert_eq!(counts, vec![1, 2, 0, 1, 1, 0, 1, 0, 1]);
+
+        assert_eq!(count_assignments(4, &[2, 2], PART_A), Some(2));
+        // x + 0 and x - 0 agree, so every zero doubles the count
+        let operators: &[&dyn Operator] = &[&Add, &Sub];
+        let inputs = [vec![5], vec![0; 100]].concat();
+        assert_eq!(count_assignments(5, &inputs, operators), Some(1 << 100));
+        let inputs = [vec![5], vec![0; 130]].concat();
+        assert_eq!(count_assignments(5, &inputs, operators), None);
+
+        for (answer, inputs) in parse_equations(INPUT) {
+            let count = count_assignments(answer, &inputs, PART_B).unwrap();
+            assert_eq!(
+                count > 0,
+                is_valid_part_b(answer, &inputs, inputs.len() - 1)
+            );
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        // results and intermediates beyond 64 bits are still exact
+        let max = i64::MAX as i128;
+        assert!(is_valid(max * 2, &[max, 2], 1));
+        assert!(is_valid(max + 1, &[max, 1], 1));
+        assert!(is_valid_part_b(max * 10 + 7, &[max, 7], 1));
+        let ops = find_witness(max * max + 1, &[max, max, 1], 2, PART_A).unwrap();
+        assert_eq!(evaluate(&[max, max, 1], &ops), Some(max * max + 1));
+        // but overflowing 128 bits is unreachable rather than wrapping or panicking
+        assert_eq!(Concat.apply(i128::MAX, 1), None);
+        assert_eq!(Mul.apply(i128::MAX, 2), None);
+        assert_eq!(try_split_tail(5, i128::MAX), None);
+        assert!(!is_valid_part_b(i128::MAX, &[i128::MAX, i128::MAX, 1], 2));
+        assert_eq!(
+            count_assignments(i128::MAX, &[i128::MAX, 1, 1], PART_B),
+            Some(1)
+        );
+        let large = [u64::MAX as i128, u64::MAX as i128, u64::MAX as i128];
+        assert!(find_precedence_witness(1, &large, &standard_precedence(PART_B)).is_none());
+
+        // zero operands and zero intermediates
+        assert!(is_valid(5, &[0, 5], 1));
+        assert!(is_valid(0, &[5, 0], 1));
+        assert!(is_valid(0, &[7, 3, 0], 2));
+        assert!(!is_valid(1, &[7, 3, 0], 2));
+        assert!(is_valid(3, &[7, 3, 0, 3], 3));
+        assert!(is_valid_part_b(50, &[5, 0], 1));
+        assert!(is_valid_part_b(500, &[5, 0, 0], 2));
+        assert!(!is_valid(50, &[5, 0], 1));
+        assert_eq!(Concat.apply(0, 7), Some(7));
+        assert_eq!(try_divide(10, 0), None);
+        // every assignment of 7 ? 3 is defined, so all of them times 0 give 0
+        assert_eq!(count_assignments(0, &[7, 3, 0], PART_A), Some(2));
+        assert_eq!(count_assignments(0, &[7, 3, 0], PART_B), Some(3));
+        let ops = find_witness(0, &[7, 3, 0], 2, PART_B).unwrap();
+        assert_eq!(evaluate(&[7, 3, 0], &ops), Some(0));
+    }
+
+    /// `+` and `*` at their standard precedence with `||` at `precedence`.
+    fn concat_at(precedence: u8) -> Vec<(&'static dyn Operator, u8)> {
+        let mut operators = standard_precedence(PART_A);
+        operators.push((&Concat, precedence));
+        operators
+    }
+
+    #[test]
+    fn precedence_evaluation() {
+        let operators = standard_precedence(PART_A);
+        let ops = find_precedence_witness(1161, &[81, 40, 27], &operators).unwrap();
+        assert_eq!(format_witness(&[81, 40, 27], &ops), "81 + 40 * 27");
+        // 11 + 6 * 16 + 20 only reaches 292 when evaluated left to right
+        assert!(find_precedence_witness(292, &[11, 6, 16, 20], &operators).is_none());
+        let ops = find_precedence_witness(386, &[11, 6, 16, 20], &operators).unwrap();
+        assert_eq!(format_witness(&[11, 6, 16, 20], &ops), "11 * 6 + 16 * 20");
+
+        // 15 || 6 * 2 is 312 when || binds tightest and 1512 when it binds loosest
+        let tight = concat_at(3);
+        let loose = concat_at(0);
+        assert!(find_precedence_witness(312, &[15, 6, 2], &tight).is_some());
+        assert!(find_precedence_witness(312, &[15, 6, 2], &loose).is_none());
+        assert!(find_precedence_witness(1512, &[15, 6, 2], &loose).is_some());
+        // equal precedence associates to the left
+        let level = concat_at(2);
+        assert!(find_precedence_witness(312, &[15, 6, 2], &level).is_some());
+        assert!(find_precedence_witness(0, &[5, 0, 3], &loose).is_some());
+
+        // operators that can shrink a partial value must not be pruned
+        let ops = find_precedence_witness(5, &[10, 5], &standard_precedence(&[&Add, &Sub]));
+        assert_eq!(format_witness(&[10, 5], &ops.unwrap()), "10 - 5");
+        let ops = find_precedence_witness(1, &[6, 3, 4], &standard_precedence(&[&Add, &Xor]));
+        // ^ binds loosest, so this is 6 ^ 7
+        assert_eq!(format_witness(&[6, 3, 4], &ops.unwrap()), "6 ^ 3 + 4");
+        assert!(
+            find_precedence_witness(2, &[6, 3, 4], &standard_precedence(&[&Add, &Xor])).is_none()
+        );
+
+        // with a single precedence level this is the left to right evaluation again
+        let flat = PART_B.iter().map(|operator| (*operator, 1)).collect_vec();
+        for (answer, inputs) in parse_equations(TEST_INPUT) {
+            assert_eq!(
+                find_precedence_witness(answer, &inputs, &flat).is_some(),
+                is_valid_part_b(answer, &inputs, inputs.len() - 1)
+            );
+        }
+        let equations = parse_equations(INPUT);
+        println!(
+            "{}",
+            solution_precedence(&equations, &standard_precedence(PART_B))
+        );
+    }
+
     const TEST_INPUT: &str = "190: 10 19
 3267: 81 40 27
 83: 17 5
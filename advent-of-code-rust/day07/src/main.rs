@@ -0,0 +1,67 @@
+use day07::{
+    Concat, PART_A, PART_B, count_assignments, find_precedence_witness, format_witness,
+    parse_equations, standard_precedence, witnesses,
+};
+use std::{env, fs, process};
+
+const INPUT: &str = include_str!("input.txt");
+
+fn main() {
+    let mut concat = false;
+    let mut count = false;
+    let mut precedence = None;
+    let mut path = None;
+    let mut args = env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--concat" => concat = true,
+            "--count" => count = true,
+            // --precedence N: evaluate with * above +, and || (with --concat) at strength N,
+            // where + is 1 and * is 2
+            "--precedence" => match args.peek().and_then(|n| n.parse().ok()) {
+                Some(n) => {
+                    precedence = Some(n);
+                    args.next();
+                }
+                None => {
+                    eprintln!("--precedence needs a number, the precedence of ||");
+                    process::exit(2);
+                }
+            },
+            _ => path = Some(arg),
+        }
+    }
+    let input = path.map_or(INPUT.to_string(), |path| fs::read_to_string(path).unwrap());
+    let equations = parse_equations(&input);
+    let operators = if concat { PART_B } else { PART_A };
+    if count {
+        for (answer, inputs) in &equations {
+            match count_assignments(*answer, inputs, operators) {
+                Some(ways) => println!("{}: {}", answer, ways),
+                None => println!("{}: overflow", answer),
+            }
+        }
+        return;
+    }
+    if let Some(precedence) = precedence {
+        let mut operators = standard_precedence(PART_A);
+        if concat {
+            operators.push((&Concat, precedence));
+        }
+        let mut total = 0;
+        for (answer, inputs) in &equations {
+            if let Some(ops) = find_precedence_witness(*answer, inputs, &operators) {
+                println!("{}: {}", answer, format_witness(inputs, &ops));
+                total += answer;
+            }
+        }
+        println!("total {}", total);
+        return;
+    }
+    let mut total = 0;
+    for (answer, expression) in witnesses(&equations, operators) {
+        println!("{}: {}", answer, expression);
+        total += answer;
+    }
+    println!("total {}", total);
+}
@@ -6,9 +6,9 @@ use std::{
 };
 
 #[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
-struct Vector {
-    x: i32,
-    y: i32,
+pub struct Vector {
+    pub x: i32,
+    pub y: i32,
 }
 
 impl Add for Vector {
@@ -40,7 +40,7 @@ fn get_a_to_b_vec(a: &Vector, b: &Vector) -> Vector {
     }
 }
 
-fn parse_grid(input: &str) -> (HashMap<char, Vec<Vector>>, Vector) {
+pub fn parse_grid(input: &str) -> (HashMap<char, Vec<Vector>>, Vector) {
     let y_len = input.lines().count();
     let x_len = input.lines().next().unwrap().len();
     let antennas = input
@@ -49,13 +49,16 @@ fn parse_grid(input: &str) -> (HashMap<char, Vec<Vector>>, Vector) {
         .flat_map(|(y, line)| {
             line.chars().enumerate().map(move |(x, c)| match c {
                 '.' => None,
-                _ => Some((c, Vector {
-                    x: x.try_into().unwrap(),
-                    y: y.try_into().unwrap(),
-                })),
+                _ => Some((
+                    c,
+                    Vector {
+                        x: x.try_into().unwrap(),
+                        y: y.try_into().unwrap(),
+                    },
+                )),
             })
         })
-        .filter_map(|x| x);
+        .flatten();
     let mut antennas_by_frequency = HashMap::new();
     for (frequency, vec) in antennas {
         antennas_by_frequency
@@ -63,54 +66,348 @@ fn parse_grid(input: &str) -> (HashMap<char, Vec<Vector>>, Vector) {
             .or_insert(vec![])
             .push(vec);
     }
-    (antennas_by_frequency, Vector {
-        x: x_len.try_into().unwrap(),
-        y: y_len.try_into().unwrap(),
-    })
+    (
+        antennas_by_frequency,
+        Vector {
+            x: x_len.try_into().unwrap(),
+            y: y_len.try_into().unwrap(),
+        },
+    )
 }
 
 fn check_valid_position_vector(p: Vector, grid_size: Vector) -> bool {
     0 <= p.x && p.x < grid_size.x && 0 <= p.y && p.y < grid_size.y
 }
 
-fn solution_a(antennas: &HashMap<char, Vec<Vector>>, grid_size: Vector) -> usize {
-    let mut unique_nodes = HashSet::new();
-    for (_, position_vectors) in antennas {
-        for (a, b) in position_vectors.iter().tuple_combinations() {
-            let a_to_b = get_a_to_b_vec(a, b);
-            let c = *a - a_to_b;
-            let d = *b + a_to_b;
-            if check_valid_position_vector(c, grid_size) {
-                unique_nodes.insert(c);
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// Where a pair of same-frequency antennas puts its antinodes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AntinodeRule {
+    /// The two points in line with the pair where one antenna is twice as far as the
+    /// other, outside the pair: `a - Δ` and `b + Δ`.
+    Outer,
+    /// The points where one antenna is twice as far as the other between the pair,
+    /// `a + Δ/3` and `a + 2Δ/3`, when they fall on the grid.
+    Trisection,
+    /// Every grid point in line with the pair, stepping by `Δ / gcd(Δ)`.
+    Line,
+}
+
+impl AntinodeRule {
+    /// The antinodes of the antennas at `a` and `b` that lie inside `grid_size`.
+    pub fn antinodes(self, a: Vector, b: Vector, grid_size: Vector) -> Vec<Vector> {
+        let a_to_b = get_a_to_b_vec(&a, &b);
+        let candidates = match self {
+            AntinodeRule::Outer => vec![a - a_to_b, b + a_to_b],
+            AntinodeRule::Trisection => {
+                if a_to_b.x % 3 != 0 || a_to_b.y % 3 != 0 {
+                    return vec![];
+                }
+                let third = Vector {
+                    x: a_to_b.x / 3,
+                    y: a_to_b.y / 3,
+                };
+                vec![a + third, b - third]
             }
-            if check_valid_position_vector(d, grid_size) {
-                unique_nodes.insert(d);
+            AntinodeRule::Line => {
+                let divisor = gcd(a_to_b.x, a_to_b.y);
+                let step = Vector {
+                    x: a_to_b.x / divisor,
+                    y: a_to_b.y / divisor,
+                };
+                let mut points = vec![];
+                let mut c = a;
+                while check_valid_position_vector(c, grid_size) {
+                    points.push(c);
+                    c = c - step;
+                }
+                let mut d = a + step;
+                while check_valid_position_vector(d, grid_size) {
+                    points.push(d);
+                    d = d + step;
+                }
+                points
             }
-        }
+        };
+        candidates
+            .into_iter()
+            .filter(|p| check_valid_position_vector(*p, grid_size))
+            .collect()
     }
-    unique_nodes.len()
 }
 
-fn solution_b(antennas: &HashMap<char, Vec<Vector>>, grid_size: Vector) -> usize {
+/// The unique antinodes of every same-frequency pair under `rule`.
+pub fn antinodes(
+    antennas: &HashMap<char, Vec<Vector>>,
+    grid_size: Vector,
+    rule: AntinodeRule,
+) -> HashSet<Vector> {
     let mut unique_nodes = HashSet::new();
-    for (_, position_vectors) in antennas {
+    for position_vectors in antennas.values() {
         for (a, b) in position_vectors.iter().tuple_combinations() {
-            let a_to_b = get_a_to_b_vec(a, b);
-            unique_nodes.insert(*a);
-            unique_nodes.insert(*b);
-            let mut c = *a - a_to_b;
-            let mut d = *b + a_to_b;
-            while check_valid_position_vector(c, grid_size) {
-                unique_nodes.insert(c);
-                c = c - a_to_b;
+            unique_nodes.extend(rule.antinodes(*a, *b, grid_size));
+        }
+    }
+    unique_nodes
+}
+
+pub fn solution_a(antennas: &HashMap<char, Vec<Vector>>, grid_size: Vector) -> usize {
+    antinodes(antennas, grid_size, AntinodeRule::Outer).len()
+}
+
+pub fn solution_b(antennas: &HashMap<char, Vec<Vector>>, grid_size: Vector) -> usize {
+    antinodes(antennas, grid_size, AntinodeRule::Line).len()
+}
+
+/// The antinodes of each frequency under `rule`, sorted by row and then column.
+pub fn antinodes_by_frequency(
+    antennas: &HashMap<char, Vec<Vector>>,
+    grid_size: Vector,
+    rule: AntinodeRule,
+) -> HashMap<char, Vec<Vector>> {
+    antennas
+        .iter()
+        .map(|(frequency, positions)| {
+            let single = HashMap::from([(*frequency, positions.clone())]);
+            let nodes = antinodes(&single, grid_size, rule)
+                .into_iter()
+                .sorted_by_key(|p| (p.y, p.x))
+                .collect();
+            (*frequency, nodes)
+        })
+        .collect()
+}
+
+const COLOURS: [u8; 6] = [31, 32, 33, 34, 35, 36];
+
+/// Draws the antenna grid with antinodes as `#`, antennas staying on top. With `layer`
+/// only that frequency and its antinodes are drawn; with `colour` every frequency gets
+/// an ANSI colour shared by its antinodes, and cells claimed by several are bold white.
+pub fn render(
+    antennas: &HashMap<char, Vec<Vector>>,
+    antinodes: &HashMap<char, Vec<Vector>>,
+    grid_size: Vector,
+    layer: Option<char>,
+    colour: bool,
+) -> String {
+    let shown = |frequency: &&char| layer.is_none_or(|only| **frequency == only);
+    let colours: HashMap<char, u8> = antennas
+        .keys()
+        .sorted()
+        .zip(COLOURS.iter().cycle())
+        .map(|(frequency, code)| (*frequency, *code))
+        .collect();
+    let mut cells: HashMap<Vector, (char, Vec<char>)> = HashMap::new();
+    for frequency in antinodes.keys().filter(shown) {
+        for node in &antinodes[frequency] {
+            cells
+                .entry(*node)
+                .or_insert(('#', vec![]))
+                .1
+                .push(*frequency);
+        }
+    }
+    for frequency in antennas.keys().filter(shown) {
+        for position in &antennas[frequency] {
+            cells.insert(*position, (*frequency, vec![*frequency]));
+        }
+    }
+
+    let mut rendered = String::new();
+    for y in 0..grid_size.y {
+        for x in 0..grid_size.x {
+            match cells.get(&Vector { x, y }) {
+                None => rendered.push('.'),
+                Some((c, _)) if !colour => rendered.push(*c),
+                Some((c, frequencies)) if frequencies.len() > 1 => {
+                    rendered += &format!("\x1b[1;37m{}\x1b[0m", c)
+                }
+                Some((c, frequencies)) => {
+                    rendered += &format!("\x1b[{}m{}\x1b[0m", colours[&frequencies[0]], c)
+                }
             }
-            while check_valid_position_vector(d, grid_size) {
-                unique_nodes.insert(d);
-                d = d + a_to_b;
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+/// `c` as a quoted JSON string.
+fn json_string(c: char) -> String {
+    match c {
+        '"' => String::from(r#""\"""#),
+        '\\' => String::from(r#""\\""#),
+        c if c.is_control() => format!(r#""\u{:04x}""#, c as u32),
+        c => format!(r#""{}""#, c),
+    }
+}
+
+/// `{"<frequency>":[{"x":..,"y":..},..],..}` with the frequencies in order.
+pub fn antinodes_json(antinodes: &HashMap<char, Vec<Vector>>) -> String {
+    let entries = antinodes
+        .iter()
+        .sorted_by_key(|(frequency, _)| **frequency)
+        .map(|(frequency, nodes)| {
+            let nodes = nodes
+                .iter()
+                .map(|p| format!(r#"{{"x":{},"y":{}}}"#, p.x, p.y))
+                .join(",");
+            format!("{}:[{}]", json_string(*frequency), nodes)
+        });
+    format!("{{{}}}", entries.collect_vec().join(",\n "))
+}
+
+/// Antennas that can be added and removed, keeping for every cell the number of pair
+/// antinodes on it so the unique antinode count is always at hand.
+pub struct AntennaMap {
+    grid_size: Vector,
+    rule: AntinodeRule,
+    antennas: HashMap<char, Vec<Vector>>,
+    occupied: HashMap<Vector, char>,
+    antinode_counts: HashMap<Vector, usize>,
+}
+
+impl AntennaMap {
+    pub fn new(grid_size: Vector, rule: AntinodeRule) -> Self {
+        AntennaMap {
+            grid_size,
+            rule,
+            antennas: HashMap::new(),
+            occupied: HashMap::new(),
+            antinode_counts: HashMap::new(),
+        }
+    }
+
+    pub fn from_antennas(
+        antennas: &HashMap<char, Vec<Vector>>,
+        grid_size: Vector,
+        rule: AntinodeRule,
+    ) -> Self {
+        let mut map = AntennaMap::new(grid_size, rule);
+        for (frequency, positions) in antennas {
+            for position in positions {
+                map.insert(*frequency, *position);
             }
         }
+        map
     }
-    unique_nodes.len()
+
+    pub fn grid_size(&self) -> Vector {
+        self.grid_size
+    }
+
+    pub fn antennas(&self) -> &HashMap<char, Vec<Vector>> {
+        &self.antennas
+    }
+
+    pub fn antenna_at(&self, position: Vector) -> Option<char> {
+        self.occupied.get(&position).copied()
+    }
+
+    /// The number of pair antinodes on `position`.
+    pub fn antinode_count(&self, position: Vector) -> usize {
+        self.antinode_counts.get(&position).copied().unwrap_or(0)
+    }
+
+    pub fn unique_antinodes(&self) -> usize {
+        self.antinode_counts.len()
+    }
+
+    pub fn antinodes(&self) -> impl Iterator<Item = &Vector> {
+        self.antinode_counts.keys()
+    }
+
+    /// Adds an antenna, returning false if `position` is off the grid or already taken.
+    pub fn insert(&mut self, frequency: char, position: Vector) -> bool {
+        if !check_valid_position_vector(position, self.grid_size)
+            || self.occupied.contains_key(&position)
+        {
+            return false;
+        }
+        let others = self.antennas.entry(frequency).or_default();
+        for other in others.iter() {
+            for node in self.rule.antinodes(*other, position, self.grid_size) {
+                *self.antinode_counts.entry(node).or_default() += 1;
+            }
+        }
+        others.push(position);
+        self.occupied.insert(position, frequency);
+        true
+    }
+
+    /// Removes the antenna at `position`, returning its frequency.
+    pub fn remove(&mut self, position: Vector) -> Option<char> {
+        let frequency = self.occupied.remove(&position)?;
+        let others = self.antennas.get_mut(&frequency).unwrap();
+        others.retain(|other| *other != position);
+        for other in others.iter() {
+            for node in self.rule.antinodes(*other, position, self.grid_size) {
+                let count = self.antinode_counts.get_mut(&node).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.antinode_counts.remove(&node);
+                }
+            }
+        }
+        if others.is_empty() {
+            self.antennas.remove(&frequency);
+        }
+        Some(frequency)
+    }
+
+    pub fn apply(&mut self, edit: Edit) -> bool {
+        match edit {
+            Edit::Insert(frequency, position) => self.insert(frequency, position),
+            Edit::Remove(position) => self.remove(position).is_some(),
+        }
+    }
+
+    /// Applies `edits` in order, returning the unique antinode count after each one.
+    pub fn replay(&mut self, edits: &[Edit]) -> Vec<usize> {
+        edits
+            .iter()
+            .map(|edit| {
+                self.apply(*edit);
+                self.unique_antinodes()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Edit {
+    Insert(char, Vector),
+    Remove(Vector),
+}
+
+/// Parses an edit log with one edit per line, `+ A 3,4` to add an antenna and `- 3,4` to
+/// remove one.
+pub fn parse_edits(input: &str) -> Vec<Edit> {
+    fn parse_position(text: &str) -> Vector {
+        let (x, y) = text.split_once(',').unwrap();
+        Vector {
+            x: x.trim().parse().unwrap(),
+            y: y.trim().parse().unwrap(),
+        }
+    }
+
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let parts = line.split_whitespace().collect_vec();
+            match parts.as_slice() {
+                ["+", frequency, position] => {
+                    Edit::Insert(frequency.chars().next().unwrap(), parse_position(position))
+                }
+                ["-", position] => Edit::Remove(parse_position(position)),
+                _ => panic!("invalid edit {:?}", line),
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -135,6 +432,165 @@ mod tests {
         println!("{}", solution_b(&antennas, grid_size));
     }
 
+    #[test]
+    fn antinode_rules() {
+        let grid_size = Vector { x: 10, y: 10 };
+        let a = Vector { x: 1, y: 1 };
+        let b = Vector { x: 7, y: 4 };
+        assert_eq!(
+            AntinodeRule::Outer.antinodes(Vector { x: 4, y: 4 }, Vector { x: 5, y: 6 }, grid_size),
+            vec![Vector { x: 3, y: 2 }, Vector { x: 6, y: 8 }]
+        );
+        assert!(AntinodeRule::Outer.antinodes(a, b, grid_size).is_empty());
+        assert_eq!(
+            AntinodeRule::Trisection.antinodes(a, b, grid_size),
+            vec![Vector { x: 3, y: 2 }, Vector { x: 5, y: 3 }]
+        );
+        assert!(
+            AntinodeRule::Trisection
+                .antinodes(a, Vector { x: 2, y: 3 }, grid_size)
+                .is_empty()
+        );
+        // Δ = (6, 3) is not primitive, so the line also passes through (3, 2) and (9, 5)
+        let line: HashSet<_> = AntinodeRule::Line
+            .antinodes(a, b, grid_size)
+            .into_iter()
+            .collect();
+        let expected: HashSet<_> = (0..5)
+            .map(|i| Vector {
+                x: 1 + 2 * i,
+                y: 1 + i,
+            })
+            .collect();
+        assert_eq!(line, expected);
+
+        let (antennas, grid_size) = parse_grid(TEST_INPUT);
+        assert!(antinodes(&antennas, grid_size, AntinodeRule::Trisection).is_empty());
+        let (antennas, grid_size) = parse_grid(INPUT);
+        println!(
+            "{}",
+            antinodes(&antennas, grid_size, AntinodeRule::Trisection).len()
+        );
+    }
+
+    #[test]
+    fn incremental_map() {
+        for rule in [
+            AntinodeRule::Outer,
+            AntinodeRule::Trisection,
+            AntinodeRule::Line,
+        ] {
+            let (antennas, grid_size) = parse_grid(INPUT);
+            let mut map = AntennaMap::from_antennas(&antennas, grid_size, rule);
+            assert_eq!(
+                map.unique_antinodes(),
+                antinodes(&antennas, grid_size, rule).len()
+            );
+            // removing every antenna one by one leaves no antinodes behind
+            let positions = antennas.values().flatten().copied().collect_vec();
+            for position in &positions {
+                assert!(map.remove(*position).is_some());
+            }
+            assert_eq!(map.unique_antinodes(), 0);
+            assert!(map.antennas().is_empty());
+        }
+
+        let (antennas, grid_size) = parse_grid(TEST_INPUT);
+        let mut map = AntennaMap::from_antennas(&antennas, grid_size, AntinodeRule::Outer);
+        assert_eq!(map.unique_antinodes(), 14);
+        assert!(!map.insert('A', Vector { x: 6, y: 5 }));
+        assert!(!map.insert('A', Vector { x: 12, y: 0 }));
+        assert_eq!(map.remove(Vector { x: 0, y: 0 }), None);
+
+        let edits = parse_edits(
+            "- 6,5
+- 8,8
+- 9,9
++ A 6,5
++ A 8,8
++ A 9,9
++ 0 0,0
+",
+        );
+        assert_eq!(edits[3], Edit::Insert('A', Vector { x: 6, y: 5 }));
+        let counts = map.replay(&edits);
+        assert_eq!(counts[2..6], [10, 10, 12, 14]);
+        assert_eq!(map.antenna_at(Vector { x: 0, y: 0 }), Some('0'));
+        assert_eq!(
+            *counts.last().unwrap(),
+            antinodes(map.antennas(), grid_size, AntinodeRule::Outer).len()
+        );
+    }
+
+    #[test]
+    fn rendering() {
+        let (antennas, grid_size) = parse_grid(TEST_INPUT);
+        let by_frequency = antinodes_by_frequency(&antennas, grid_size, AntinodeRule::Outer);
+        let total = by_frequency
+            .values()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .len();
+        assert_eq!(total, 14);
+        assert_eq!(
+            render(&antennas, &by_frequency, grid_size, None, false),
+            "......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.
+"
+        );
+        assert_eq!(
+            render(&antennas, &by_frequency, grid_size, Some('A'), false),
+            "............
+...#........
+....#.......
+............
+............
+......A.....
+............
+.......#....
+........A...
+.........A..
+..........#.
+..........#.
+"
+        );
+        let coloured = render(&antennas, &by_frequency, grid_size, None, true);
+        assert!(coloured.contains("\x1b[32mA\x1b[0m"));
+        assert!(coloured.contains("\x1b[31m0\x1b[0m"));
+        // the antinode at (6, 0) is only from 0
+        assert!(coloured.starts_with("......\x1b[31m#\x1b[0m"));
+
+        let json = antinodes_json(&by_frequency);
+        assert!(json.starts_with(r#"{"0":[{"x":6,"y":0},{"x":11,"y":0},"#));
+        assert!(json.contains(r#""A":[{"x":3,"y":1},{"x":4,"y":2},{"x":7,"y":7},"#));
+
+        let (antennas, grid_size) = parse_grid("...\\\n.\".\\\n..\".\n....");
+        let by_frequency = antinodes_by_frequency(&antennas, grid_size, AntinodeRule::Outer);
+        assert_eq!(
+            antinodes_json(&by_frequency),
+            r#"{"\"":[{"x":0,"y":0},{"x":3,"y":3}],
+ "\\":[{"x":3,"y":2}]}"#
+        );
+        assert_eq!(json_string('\t'), r#""\u0009""#);
+
+        let (antennas, grid_size) = parse_grid(INPUT);
+        let by_frequency = antinodes_by_frequency(&antennas, grid_size, AntinodeRule::Line);
+        println!(
+            "{}",
+            render(&antennas, &by_frequency, grid_size, None, true)
+        );
+    }
+
     const TEST_INPUT: &str = "............
 ........0...
 .....0......
@@ -0,0 +1,67 @@
+use day08::{
+    AntennaMap, AntinodeRule, antinodes_by_frequency, antinodes_json, parse_edits, parse_grid,
+    render,
+};
+use std::{env, fs};
+
+const INPUT: &str = include_str!("input.txt");
+
+fn main() {
+    let mut rule = AntinodeRule::Outer;
+    let mut replay = None;
+    let mut draw = false;
+    let mut layer = None;
+    let mut colour = false;
+    let mut json = false;
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rule" => {
+                rule = match args.next().as_deref() {
+                    Some("outer") => AntinodeRule::Outer,
+                    Some("trisection") => AntinodeRule::Trisection,
+                    Some("line") => AntinodeRule::Line,
+                    other => panic!("unknown rule {:?}", other),
+                }
+            }
+            // --replay edits.txt: apply an edit log and print the count after each edit
+            "--replay" => replay = args.next(),
+            "--render" => draw = true,
+            // --layer A: only draw frequency A and its antinodes
+            "--layer" => layer = args.next().and_then(|frequency| frequency.chars().next()),
+            "--colour" => colour = true,
+            "--json" => json = true,
+            _ => path = Some(arg),
+        }
+    }
+    let input = path.map_or(INPUT.to_string(), |path| fs::read_to_string(path).unwrap());
+    let (antennas, grid_size) = parse_grid(input.trim_end());
+    if draw || json {
+        let by_frequency = antinodes_by_frequency(&antennas, grid_size, rule);
+        if draw {
+            print!(
+                "{}",
+                render(&antennas, &by_frequency, grid_size, layer, colour)
+            );
+        }
+        if json {
+            println!("{}", antinodes_json(&by_frequency));
+        }
+        return;
+    }
+    let mut map = AntennaMap::from_antennas(&antennas, grid_size, rule);
+    println!("{}", map.unique_antinodes());
+    if let Some(replay) = replay {
+        let log = fs::read_to_string(replay).unwrap();
+        for edit in parse_edits(&log) {
+            let applied = map.apply(edit);
+            println!(
+                "{:?}{}: {}",
+                edit,
+                if applied { "" } else { " (ignored)" },
+                map.unique_antinodes()
+            );
+        }
+    }
+}
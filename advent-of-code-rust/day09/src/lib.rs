@@ -1,6 +1,6 @@
 #![allow(unused)]
 use itertools::Itertools;
-use std::str::FromStr;
+use std::{cmp::Reverse, collections::BinaryHeap, str::FromStr};
 
 #[derive(Debug, Clone)]
 enum Block {
@@ -8,13 +8,69 @@ enum Block {
     Empty,
 }
 
-fn parse(input: &str) -> Vec<Block> {
-    let digits = input
-        .replace("\r\n", "")
+fn parse_digits(input: &str) -> Vec<u32> {
+    input
+        .trim_end()
         .chars()
         .map(|c| c.to_digit(10).unwrap())
-        .collect();
-    expand_disk(digits)
+        .collect()
+}
+
+fn parse(input: &str) -> Vec<Block> {
+    expand_disk(parse_digits(input))
+}
+
+/// A run of `len` blocks of file `id` starting at block `start`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub id: usize,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Span {
+    /// The checksum contribution of the span, `id * (start + .. + start + len - 1)`.
+    pub fn check_sum(&self) -> usize {
+        self.id * (2 * self.start + self.len).saturating_sub(1) * self.len / 2
+    }
+}
+
+/// A run of `len` free blocks starting at block `start`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Gap {
+    pub start: usize,
+    pub len: usize,
+}
+
+/// The disk map as file spans and the gaps between them, without expanding it into blocks.
+#[derive(Debug, Clone)]
+pub struct DiskMap {
+    pub files: Vec<Span>,
+    pub gaps: Vec<Gap>,
+}
+
+pub fn parse_disk_map(input: &str) -> DiskMap {
+    let mut files = Vec::new();
+    let mut gaps = Vec::new();
+    let mut start = 0;
+    for (position, digit) in parse_digits(input).into_iter().enumerate() {
+        let len = digit as usize;
+        if position % 2 != 0 {
+            gaps.push(Gap { start, len });
+        } else {
+            files.push(Span {
+                id: files.len(),
+                start,
+                len,
+            });
+        }
+        start += len;
+    }
+    DiskMap { files, gaps }
+}
+
+pub fn span_check_sum(spans: &[Span]) -> usize {
+    spans.iter().map(Span::check_sum).sum()
 }
 
 fn expand_disk(input: Vec<u32>) -> Vec<Block> {
@@ -48,7 +104,7 @@ fn compact_disk(disk: Vec<Block>) -> Vec<Block> {
     for (right_idx, block) in disk.iter().enumerate().rev() {
         match block {
             Block::File { id } => {
-                while (!is_empty(&disk[left_idx])) {
+                while (left_idx < disk.len() && !is_empty(&disk[left_idx])) {
                     left_idx += 1;
                 }
                 if (left_idx >= right_idx) {
@@ -73,8 +129,44 @@ fn check_sum(disk: Vec<Block>) -> usize {
         .sum()
 }
 
-fn solution_a(disk: Vec<Block>) -> usize {
-    check_sum(compact_disk(disk))
+/// `compact_disk` on spans: the last file blocks move one gap-sized piece at a time into
+/// the leftmost free blocks, until the free space is no longer left of the file blocks.
+pub fn compact_spans(disk: &DiskMap) -> Vec<Span> {
+    let mut files = disk.files.clone();
+    let mut gaps = disk.gaps.clone();
+    let mut moved = Vec::new();
+    let mut gap_idx = 0;
+    'files: for file in files.iter_mut().rev() {
+        while file.len > 0 {
+            while gaps.get(gap_idx).is_some_and(|gap| gap.len == 0) {
+                gap_idx += 1;
+            }
+            let Some(gap) = gaps.get_mut(gap_idx) else {
+                break 'files;
+            };
+            let last = file.start + file.len - 1;
+            if gap.start >= last {
+                break 'files;
+            }
+            // blocks only move while the free block is left of the file block
+            let len = gap.len.min(file.len).min((last - gap.start).div_ceil(2));
+            moved.push(Span {
+                id: file.id,
+                start: gap.start,
+                len,
+            });
+            gap.start += len;
+            gap.len -= len;
+            file.len -= len;
+        }
+    }
+    files.retain(|file| file.len > 0);
+    files.extend(moved);
+    files
+}
+
+pub fn solution_a(disk: &DiskMap) -> usize {
+    span_check_sum(&compact_spans(disk))
 }
 
 fn compact_disk_b(disk: Vec<Block>) -> Vec<Block> {
@@ -115,7 +207,7 @@ fn compact_disk_b(disk: Vec<Block>) -> Vec<Block> {
     compacted
 }
 
-fn get_chunks(disk: &Vec<Block>) -> impl DoubleEndedIterator<Item = &[Block]> {
+fn get_chunks(disk: &[Block]) -> impl DoubleEndedIterator<Item = &[Block]> {
     disk.chunk_by(|a, b| match (a, b) {
         (Block::File { id: id1 }, Block::File { id: id2 }) => id1 == id2,
         (Block::Empty, Block::Empty) => true,
@@ -123,11 +215,39 @@ fn get_chunks(disk: &Vec<Block>) -> impl DoubleEndedIterator<Item = &[Block]> {
     })
 }
 
-fn solution_b(disk: Vec<Block>) -> usize {
-    check_sum(compact_disk_b(disk))
+/// `compact_disk_b` on spans: every file, from the highest id down, moves whole into the
+/// leftmost gap before it that is large enough. Gaps are at most 9 blocks long, so they
+/// sit in one min-heap of starts per length, and the leftmost fitting gap is the smallest
+/// top among the heaps of lengths at least the file length.
+pub fn compact_spans_whole(disk: &DiskMap) -> Vec<Span> {
+    let mut files = disk.files.clone();
+    let mut gaps_by_len: [BinaryHeap<Reverse<usize>>; 10] = Default::default();
+    for gap in &disk.gaps {
+        gaps_by_len[gap.len].push(Reverse(gap.start));
+    }
+    for file in files.iter_mut().rev() {
+        let Some(len) = (file.len..gaps_by_len.len())
+            .filter(|len| {
+                gaps_by_len[*len]
+                    .peek()
+                    .is_some_and(|Reverse(start)| *start < file.start)
+            })
+            .min_by_key(|len| gaps_by_len[*len].peek().map(|Reverse(start)| *start))
+        else {
+            continue;
+        };
+        let Reverse(start) = gaps_by_len[len].pop().unwrap();
+        file.start = start;
+        gaps_by_len[len - file.len].push(Reverse(start + file.len));
+    }
+    files
+}
+
+pub fn solution_b(disk: &DiskMap) -> usize {
+    span_check_sum(&compact_spans_whole(disk))
 }
 
-fn pretty_print_disk(disk: &Vec<Block>) {
+fn pretty_print_disk(disk: &[Block]) {
     let pretty_repr = disk
         .iter()
         .map(|block| match block {
@@ -144,20 +264,89 @@ mod tests {
 
     #[test]
     fn it_works() {
-        let disk = parse(TEST_INPUT_A1);
-        assert_eq!(solution_a(disk), 60);
+        let disk = parse_disk_map(TEST_INPUT_A1);
+        assert_eq!(solution_a(&disk), 60);
 
-        let disk = parse(TEST_INPUT_A2);
-        assert_eq!(solution_a(disk), 1928);
+        let disk = parse_disk_map(TEST_INPUT_A2);
+        assert_eq!(solution_a(&disk), 1928);
 
-        let disk = parse(INPUT);
-        println!("{}", solution_a(disk));
+        let disk = parse_disk_map(INPUT);
+        println!("{}", solution_a(&disk));
 
-        let disk = parse(TEST_INPUT_A2);
-        assert_eq!(solution_b(disk), 2858);
+        let disk = parse_disk_map(TEST_INPUT_A2);
+        assert_eq!(solution_b(&disk), 2858);
 
-        let disk = parse(INPUT);
-        println!("{}", solution_b(disk));
+        let disk = parse_disk_map(INPUT);
+        println!("{}", solution_b(&disk));
+    }
+
+    /// The linear scan over the gaps that `compact_spans_whole` replaced.
+    fn compact_spans_linear(disk: &DiskMap) -> Vec<Span> {
+        let mut files = disk.files.clone();
+        let mut gaps = disk.gaps.clone();
+        for file in files.iter_mut().rev() {
+            if let Some(gap) = gaps
+                .iter_mut()
+                .take_while(|gap| gap.start < file.start)
+                .find(|gap| gap.len >= file.len)
+            {
+                file.start = gap.start;
+                gap.start += file.len;
+                gap.len -= file.len;
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn spans_match_blocks() {
+        for input in [
+            TEST_INPUT_A1,
+            TEST_INPUT_A2,
+            "1010101010101010101",
+            "9",
+            "90909",
+            "19191",
+            "2333133121414131402\n",
+            INPUT,
+        ] {
+            let disk = parse_disk_map(input);
+            assert_eq!(
+                solution_a(&disk),
+                check_sum(compact_disk(parse(input))),
+                "{}",
+                input
+            );
+            assert_eq!(
+                solution_b(&disk),
+                check_sum(compact_disk_b(parse(input))),
+                "{}",
+                input
+            );
+            let mut heaps = compact_spans_whole(&disk);
+            let mut linear = compact_spans_linear(&disk);
+            heaps.sort_by_key(|span| span.id);
+            linear.sort_by_key(|span| span.id);
+            assert_eq!(heaps, linear, "{}", input);
+        }
+        assert_eq!(
+            Span {
+                id: 3,
+                start: 4,
+                len: 3
+            }
+            .check_sum(),
+            3 * (4 + 5 + 6)
+        );
+        assert_eq!(
+            Span {
+                id: 3,
+                start: 0,
+                len: 0
+            }
+            .check_sum(),
+            0
+        );
     }
 
     const TEST_INPUT_A1: &str = "12345";